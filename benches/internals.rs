@@ -0,0 +1,219 @@
+//! Micro-benchmarks of solver internals, run with `cargo bench`.
+//! Boards are generated from fixed seed (see [spellcast_solver::random::generate_boards]) and dictionary is dictionary.txt, so every run measures the same work.
+//! After benchmarks finish, mean times are compared with benches/baseline.json and the run fails if any benchmark got slower than allowed there.
+//! Baseline depends on the machine, so record your own before comparing changes: `UPDATE_BASELINE=1 cargo bench`.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use criterion::{black_box, BatchSize, Criterion};
+use spellcast_solver::{
+    dictionary::{load_dictionary_bytes, load_dictionary_tree},
+    random::{generate_boards, LetterDistribution},
+    spellcast::{solver_wrapper, Board, SolveControl, SolveOptions, SortedWordVec, Word},
+};
+
+const BASELINE_PATH: &str = "benches/baseline.json";
+const SEED: u64 = 0;
+const BOARD_COUNT: usize = 100;
+
+/// Stored baseline, mean times of benchmarks in nanoseconds.
+#[derive(serde::Deserialize, serde::Serialize)]
+struct Baseline {
+    /// Benchmark fails if its mean time is higher than baseline multiplied by this.
+    max_slowdown: f64,
+    mean_ns: BTreeMap<String, f64>,
+}
+
+/// Returns board strings of seeded random boards.
+fn board_strings() -> Vec<String> {
+    generate_boards(SEED, BOARD_COUNT, 5, &LetterDistribution::default())
+        .expect("random boards should be valid")
+        .iter()
+        .map(|x| x.to_string())
+        .collect()
+}
+
+fn benchmarks(criterion: &mut Criterion) {
+    let dictionary_text =
+        std::fs::read_to_string("dictionary.txt").expect("dictionary.txt should be readable");
+    let dictionary = load_dictionary_tree(dictionary_text.clone());
+    let binary = bincode::serialize(&dictionary).expect("dictionary should serialise");
+    let board_strings = board_strings();
+    let boards: Vec<Board> = board_strings
+        .iter()
+        .map(|x| x.parse().expect("random board should be valid"))
+        .collect();
+    // Words (with their boards) found on random boards with 1 swap, used as input for Word::new and SortedWordVec::push.
+    let words: Vec<(&Board, Word)> = boards
+        .iter()
+        .flat_map(|board| {
+            solver_wrapper(
+                board,
+                1,
+                1,
+                &dictionary,
+                u8::MAX,
+                &SolveOptions::default(),
+                SolveControl::default(),
+            )
+            .into_iter()
+            .map(move |word| (board, word))
+        })
+        .collect();
+
+    // Whole solves of seeded random boards with English letter frequencies, fewer boards for more swaps so each iteration stays short.
+    let english: LetterDistribution = "english".parse().expect("distribution should be valid");
+    let solver_boards =
+        generate_boards(SEED, 100, 5, &english).expect("random boards should be valid");
+    let mut solver = criterion.benchmark_group("solver");
+    solver.sample_size(10);
+    for (name, swaps, count) in [("0_swaps", 0, 100), ("1_swap", 1, 10), ("2_swaps", 2, 2)] {
+        solver.bench_function(name, |b| {
+            b.iter(|| {
+                for board in &solver_boards[..count] {
+                    black_box(solver_wrapper(
+                        board,
+                        swaps,
+                        1,
+                        &dictionary,
+                        5,
+                        &SolveOptions::default(),
+                        SolveControl::default(),
+                    ));
+                }
+            })
+        });
+    }
+    solver.finish();
+    let mut slow = criterion.benchmark_group("dictionary");
+    slow.sample_size(10);
+    slow.bench_function("load_dictionary_tree", |b| {
+        b.iter_batched(
+            || dictionary_text.clone(),
+            load_dictionary_tree,
+            BatchSize::LargeInput,
+        )
+    });
+    slow.bench_function("bincode_cache_load", |b| {
+        b.iter(|| load_dictionary_bytes(black_box(&binary)))
+    });
+    slow.finish();
+    criterion.bench_function("board_from_str", |b| {
+        b.iter(|| {
+            for board in &board_strings {
+                black_box(board.parse::<Board>().ok());
+            }
+        })
+    });
+    criterion.bench_function("word_new", |b| {
+        b.iter(|| {
+            for (board, word) in &words {
+                black_box(Word::new(word.steps.clone(), board));
+            }
+        })
+    });
+    criterion.bench_function("sorted_word_vec_push", |b| {
+        b.iter_batched(
+            || words.clone(),
+            |words| {
+                let mut sorted = SortedWordVec::new();
+                for (board, word) in words {
+                    sorted.push(word, board);
+                }
+                sorted
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+/// Returns mean times of benchmarks that ran after `since` (so benchmarks skipped by filter aren't compared), read from criterion output.
+fn measured(since: SystemTime) -> BTreeMap<String, f64> {
+    let target =
+        std::env::var_os("CARGO_TARGET_DIR").map_or(PathBuf::from("target"), PathBuf::from);
+    let mut results = BTreeMap::new();
+    for name in [
+        "dictionary/load_dictionary_tree",
+        "dictionary/bincode_cache_load",
+        "board_from_str",
+        "word_new",
+        "sorted_word_vec_push",
+        "solver/0_swaps",
+        "solver/1_swap",
+        "solver/2_swaps",
+    ] {
+        let path = target
+            .join("criterion")
+            .join(Path::new(name))
+            .join("new/estimates.json");
+        let Ok(modified) = std::fs::metadata(&path).and_then(|x| x.modified()) else {
+            continue;
+        };
+        if modified < since {
+            continue;
+        }
+        let estimates: serde_json::Value = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+            Err(_) => continue,
+        };
+        if let Some(mean) = estimates["mean"]["point_estimate"].as_f64() {
+            results.insert(name.to_owned(), mean);
+        }
+    }
+    results
+}
+
+/// Compares measured times with the baseline (or updates it with `UPDATE_BASELINE=1`), exits with error if anything regressed.
+fn check_baseline(measured: BTreeMap<String, f64>) {
+    let mut baseline: Baseline = std::fs::read_to_string(BASELINE_PATH)
+        .ok()
+        .and_then(|x| serde_json::from_str(&x).ok())
+        .unwrap_or(Baseline {
+            max_slowdown: 1.25,
+            mean_ns: BTreeMap::new(),
+        });
+    if std::env::var_os("UPDATE_BASELINE").is_some() {
+        baseline.mean_ns.extend(measured);
+        let json = serde_json::to_string_pretty(&baseline).expect("baseline should serialise");
+        std::fs::write(BASELINE_PATH, json + "\n").expect("baseline should be writable");
+        println!("Updated {BASELINE_PATH}");
+        return;
+    }
+    let mut regressions = 0;
+    for (name, mean) in &measured {
+        let Some(expected) = baseline.mean_ns.get(name) else {
+            println!("{name}: no baseline");
+            continue;
+        };
+        let ratio = mean / expected;
+        let verdict = if ratio > baseline.max_slowdown {
+            regressions += 1;
+            "REGRESSION"
+        } else {
+            "ok"
+        };
+        println!("{name}: {ratio:.2}x baseline ({verdict})");
+    }
+    if regressions > 0 {
+        eprintln!(
+            "{regressions} benchmark(s) are more than {:.2}x slower than {BASELINE_PATH}",
+            baseline.max_slowdown
+        );
+        std::process::exit(1);
+    }
+}
+
+fn main() {
+    let start = SystemTime::now();
+    let mut criterion = Criterion::default().configure_from_args();
+    benchmarks(&mut criterion);
+    criterion.final_summary();
+    // `cargo test --benches` runs every benchmark only once without measuring, so there is nothing to compare.
+    if std::env::args().any(|x| x == "--bench") {
+        check_baseline(measured(start));
+    }
+}
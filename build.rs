@@ -0,0 +1,12 @@
+fn main() {
+    // Header only changes together with src/ffi.rs, so it is only regenerated on request (--features header).
+    #[cfg(feature = "header")]
+    {
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").expect("cargo should set manifest dir");
+        cbindgen::generate(&crate_dir)
+            .expect("header should be generated")
+            .write_to_file(format!("{crate_dir}/include/spellcast_solver.h"));
+    }
+    println!("cargo::rerun-if-changed=src/ffi.rs");
+    println!("cargo::rerun-if-changed=cbindgen.toml");
+}
@@ -0,0 +1,32 @@
+//! Fuzz target for board parsers: board string (lenient and strict) and JSON board.
+//! Parsers must never panic, and canonical board string of any parsed board must parse back into the same board.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use spellcast_solver::spellcast::Board;
+
+fuzz_target!(|data: &str| {
+    if let Ok(board) = data.parse::<Board>() {
+        let canonical = board.to_string();
+        let reparsed: Board = canonical
+            .parse()
+            .expect("canonical board string should be valid");
+        assert_eq!(reparsed.to_string(), canonical);
+        assert_eq!(
+            (reparsed.width(), reparsed.height()),
+            (board.width(), board.height())
+        );
+        assert!(Board::parse_strict(&canonical).is_ok());
+    }
+    let _ = Board::parse_strict(data);
+    if let Ok((board, _)) = Board::from_json(data) {
+        let canonical = board.to_string();
+        let reparsed: Board = canonical
+            .parse()
+            .expect("canonical string of JSON board should be valid");
+        assert_eq!(
+            (reparsed.width(), reparsed.height()),
+            (board.width(), board.height())
+        );
+    }
+});
@@ -0,0 +1,17 @@
+//! Fuzz target for dictionary parsers: dictionary file and its binary cache (both through `load_dictionary_bytes`).
+//! Parsers must never panic, and dictionary file can't produce more words than it has lines.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use spellcast_solver::dictionary::{count_words, load_dictionary_bytes};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(dictionary) = load_dictionary_bytes(data) {
+        let words = count_words(&dictionary);
+        if let Ok(text) = std::str::from_utf8(data) {
+            if !data.iter().take(8).any(|x| *x == 0) {
+                assert!(words <= text.lines().count());
+            }
+        }
+    }
+});
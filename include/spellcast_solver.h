@@ -0,0 +1,130 @@
+#ifndef SPELLCAST_SOLVER_H
+#define SPELLCAST_SOLVER_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit (regenerate with `cargo build --features header`). */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Board parsed with [spellcast_board_parse].
+typedef struct SpellcastBoard SpellcastBoard;
+
+// Dictionary loaded with [spellcast_dictionary_load].
+typedef struct SpellcastDictionary SpellcastDictionary;
+
+// Results of [spellcast_solve].
+typedef struct SpellcastResults SpellcastResults;
+
+// Solve options, mirroring CLI arguments (see CLI.md).
+// Use [spellcast_options_default] to get defaults and change only what you need.
+typedef struct SpellcastOptions {
+  // Number of swaps to consider.
+  uint8_t swaps;
+  // Number of top moves to return.
+  uint8_t move_count;
+  // Number of threads to use.
+  uint8_t threads;
+  // Minimum word length.
+  uint8_t min_length;
+  // Maximum word length, 0 for no limit.
+  uint8_t max_length;
+  // Tiles that must be used, e.g. `A1,C3` (NULL for none).
+  const char *include;
+  // Tiles that must not be used (NULL for none).
+  const char *exclude;
+  // Tiles word can start on (NULL for any).
+  const char *start;
+  // Tiles word can end on (NULL for any).
+  const char *end;
+  // Pattern words must match, e.g. `*qu*` (NULL for none).
+  const char *pattern;
+  // Regex words must match (NULL for none).
+  const char *regex;
+  // Adjacency rule name, e.g. `4way` (NULL for 8way).
+  const char *adjacency;
+  // How word multipliers stack, `max` or `multiply` (NULL for max).
+  const char *multiplier_rule;
+} SpellcastOptions;
+
+// Single word of [SpellcastResults], pointers are valid until results are freed.
+typedef struct SpellcastWord {
+  // Word itself (lowercase).
+  const char *word;
+  uint16_t score;
+  uint8_t gems_collected;
+  uint8_t swaps_used;
+  // Tile indices of the path (row by row from top left), `tile_count` items.
+  const uint16_t *tiles;
+  size_t tile_count;
+} SpellcastWord;
+
+// Returns error message of the last failed call on this thread (NULL if there is none).
+// It is valid until next failed call on the same thread.
+const char *spellcast_last_error(void);
+
+// Loads dictionary file (same as `-d`), returns NULL on failure.
+//
+// # Safety
+// `path` must be valid NUL-terminated string.
+struct SpellcastDictionary *spellcast_dictionary_load(const char *path);
+
+// Frees dictionary, NULL is ignored.
+//
+// # Safety
+// `dictionary` must be NULL or returned by [spellcast_dictionary_load] and not freed yet.
+void spellcast_dictionary_free(struct SpellcastDictionary *dictionary);
+
+// Parses board string (same as `-b`), returns NULL on failure.
+//
+// # Safety
+// `board` must be valid NUL-terminated string.
+struct SpellcastBoard *spellcast_board_parse(const char *board);
+
+// Frees board, NULL is ignored.
+//
+// # Safety
+// `board` must be NULL or returned by [spellcast_board_parse] and not freed yet.
+void spellcast_board_free(struct SpellcastBoard *board);
+
+// Returns default options (same as CLI defaults).
+struct SpellcastOptions spellcast_options_default(void);
+
+// Solves the board, returns NULL on failure.
+// `options` can be NULL to use defaults.
+//
+// # Safety
+// `dictionary` and `board` must be valid handles, `options` must be NULL or point to valid options.
+struct SpellcastResults *spellcast_solve(const struct SpellcastDictionary *dictionary,
+                                         const struct SpellcastBoard *board,
+                                         const struct SpellcastOptions *options);
+
+// Returns number of words in results.
+//
+// # Safety
+// `results` must be valid handle.
+size_t spellcast_results_len(const struct SpellcastResults *results);
+
+// Writes word at `index` (best word first) to `word`, returns false if index is out of range.
+//
+// # Safety
+// `results` must be valid handle and `word` must point to writable [SpellcastWord].
+bool spellcast_results_get(const struct SpellcastResults *results,
+                           size_t index,
+                           struct SpellcastWord *word);
+
+// Returns results as JSON, same as `-f json` output (see CLI.md), valid until results are freed.
+//
+// # Safety
+// `results` must be valid handle.
+const char *spellcast_results_json(const struct SpellcastResults *results);
+
+// Frees results, NULL is ignored.
+//
+// # Safety
+// `results` must be NULL or returned by [spellcast_solve] and not freed yet.
+void spellcast_results_free(struct SpellcastResults *results);
+
+#endif  /* SPELLCAST_SOLVER_H */
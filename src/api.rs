@@ -0,0 +1,275 @@
+use std::{
+    collections::HashSet,
+    sync::atomic::AtomicBool,
+    time::{Duration, Instant},
+};
+
+use serde_json::Value;
+
+use crate::{
+    dictionary::Node,
+    output::{JsonOutput, Timings},
+    spellcast::{
+        compile_regex, pattern_to_regex, solver_wrapper, Adjacency, Board, SolveControl,
+        SolveOptions, Word, WordMultiplierRule,
+    },
+    utils::{MAX_SERVER_SWAPS, SERVER_TIME_LIMIT},
+};
+
+/// Adjacency in solve request, either rule name (e.g. `4way`) or custom adjacency list.
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+pub enum AdjacencyValue {
+    Name(String),
+    List(Vec<Vec<u16>>),
+}
+
+/// Solve request used by server modes.
+/// Its fields mirror CLI arguments (see CLI.md), except that word list is an array of words and custom adjacency list is an array of arrays of tile indices.
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SolveRequest {
+    /// Arbitrary value that is copied to the response, so responses can be matched with requests.
+    #[serde(default)]
+    pub id: Value,
+    /// Board string or JSON board (see [Board::from_json]).
+    pub board: Value,
+    pub swaps: Option<u8>,
+    #[serde(default = "SolveRequest::default_count")]
+    pub count: u8,
+    #[serde(default)]
+    pub strict: bool,
+    pub adjacency: Option<AdjacencyValue>,
+    pub multiplier_rule: Option<String>,
+    pub include: Option<String>,
+    pub exclude: Option<String>,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub min_length: Option<u8>,
+    pub max_length: Option<u8>,
+    pub pattern: Option<String>,
+    pub regex: Option<String>,
+    pub word_list: Option<Vec<String>>,
+}
+
+impl SolveRequest {
+    /// Default move count for serde, same as in CLI.
+    pub fn default_count() -> u8 {
+        5
+    }
+
+    /// Parses board, swap count and options from the request.
+    pub fn prepare(&self) -> Result<(Board, u8, SolveOptions), String> {
+        let (board, board_swaps) = match &self.board {
+            Value::String(board) if self.strict => (Board::parse_strict(board)?, None),
+            Value::String(board) => (board.parse()?, None),
+            board => Board::from_json_value(board.clone())?,
+        };
+        self.configure(board, board_swaps)
+    }
+
+    /// Applies the request (except for its board) to already parsed board and returns swap count and options.
+    /// `board_swaps` is swap count specified by the board itself, it is used if request doesn't specify it.
+    pub fn configure(
+        &self,
+        mut board: Board,
+        board_swaps: Option<u8>,
+    ) -> Result<(Board, u8, SolveOptions), String> {
+        if let Some(adjacency) = &self.adjacency {
+            board.set_adjacency(&match adjacency {
+                AdjacencyValue::Name(name) => Adjacency::from_name(name)?,
+                AdjacencyValue::List(list) => Adjacency::Custom(list.clone()),
+            })?;
+        }
+        if let Some(rule) = &self.multiplier_rule {
+            board.set_word_multiplier_rule(rule.parse::<WordMultiplierRule>()?);
+        }
+        let tiles = |value: &Option<String>| match value {
+            Some(value) => board.parse_tiles(value),
+            None => Ok(vec![]),
+        };
+        let mut patterns = vec![];
+        if let Some(pattern) = &self.pattern {
+            patterns.push(pattern_to_regex(pattern)?);
+        }
+        if let Some(regex) = &self.regex {
+            patterns.push(compile_regex(regex)?);
+        }
+        let options = SolveOptions {
+            include: tiles(&self.include)?,
+            exclude: tiles(&self.exclude)?,
+            start: tiles(&self.start)?,
+            end: tiles(&self.end)?,
+            min_length: self.min_length.unwrap_or(0),
+            max_length: self.max_length,
+            patterns,
+            word_list: self.word_list.as_ref().map(|words| {
+                words
+                    .iter()
+                    .map(|x| x.trim().to_ascii_lowercase())
+                    .collect::<HashSet<_>>()
+            }),
+        };
+        let swaps = self.swaps.or(board_swaps).unwrap_or(0);
+        Ok((board, swaps, options))
+    }
+}
+
+/// Response that is sent instead of [JsonOutput] when request can't be handled.
+#[derive(serde::Serialize)]
+struct ErrorResponse<'a> {
+    #[serde(skip_serializing_if = "Value::is_null")]
+    id: &'a Value,
+    error: String,
+}
+
+/// Successful response, [JsonOutput] with request id.
+#[derive(serde::Serialize)]
+struct Response<'a> {
+    #[serde(skip_serializing_if = "Value::is_null")]
+    id: &'a Value,
+    /// Whether this is best-so-far result sent while the search is still running.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    partial: bool,
+    /// Whether search was stopped by [Solver::time_limit], so better words may exist.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    timed_out: bool,
+    #[serde(flatten)]
+    output: JsonOutput<'a>,
+}
+
+/// Information about loaded dictionary.
+#[derive(serde::Serialize)]
+pub struct DictionaryInfo {
+    /// Path dictionary was loaded from.
+    pub path: String,
+    /// Number of words in dictionary (only words with 3 or more characters are loaded).
+    pub words: usize,
+    /// Time spent loading the dictionary.
+    pub load_ms: f64,
+}
+
+/// Solver state shared by all requests, so dictionary is only loaded once and threads are reused.
+pub struct Solver {
+    pub dictionary: &'static Vec<(char, Node)>,
+    pub dictionary_info: DictionaryInfo,
+    /// Number of threads used for each request.
+    pub threads: u8,
+    /// Maximum number of swaps request can ask for, requests with more swaps get an error.
+    pub max_swaps: u8,
+    /// Time limit of each request, search stops once it is reached and best words found by then are returned.
+    pub time_limit: Option<Duration>,
+    /// Worker threads shared by all requests (only with more than one thread).
+    pool: Option<rayon_core::ThreadPool>,
+}
+
+impl Solver {
+    /// Creates solver that uses `threads` threads for each request.
+    /// Requests are limited to [MAX_SERVER_SWAPS] swaps and [SERVER_TIME_LIMIT] seconds, change [Solver::max_swaps] and [Solver::time_limit] to use other limits.
+    /// With more than one thread, they are started once here and shared by all requests, so concurrent requests take turns using them.
+    pub fn new(
+        dictionary: &'static Vec<(char, Node)>,
+        dictionary_info: DictionaryInfo,
+        threads: u8,
+    ) -> Result<Solver, String> {
+        let pool = match threads {
+            0 | 1 => None,
+            _ => Some(
+                rayon_core::ThreadPoolBuilder::new()
+                    .num_threads(threads as usize)
+                    .thread_name(|index| format!("solver-{index}"))
+                    .build()
+                    .map_err(|e| format!("Failed to start solver threads: {e}"))?,
+            ),
+        };
+        Ok(Solver {
+            dictionary,
+            dictionary_info,
+            threads,
+            max_swaps: MAX_SERVER_SWAPS,
+            time_limit: Some(Duration::from_secs_f64(SERVER_TIME_LIMIT)),
+            pool,
+        })
+    }
+
+    /// Handles single JSON request and returns JSON response (without trailing newline).
+    /// Errors (including invalid JSON) are returned as `Err` with `{"id":...,"error":"..."}` response.
+    pub fn handle(&self, request: &str) -> Result<String, String> {
+        self.handle_live(request, None, None)
+    }
+
+    /// Same as [Solver::handle], but search also stops early once `cancel` is set and `partial` is called with best-so-far responses (with `"partial":true`) during the search.
+    pub fn handle_live(
+        &self,
+        request: &str,
+        cancel: Option<&AtomicBool>,
+        partial: Option<&(dyn Fn(String) + Sync)>,
+    ) -> Result<String, String> {
+        let request: SolveRequest = match serde_json::from_str(request) {
+            Ok(request) => request,
+            Err(e) => {
+                // Try to at least get the id, so client knows which request failed.
+                let id = serde_json::from_str::<Value>(request)
+                    .ok()
+                    .and_then(|x| x.get("id").cloned())
+                    .unwrap_or_default();
+                return Err(error_response(&id, format!("Invalid request: {e}")));
+            }
+        };
+        let (board, swaps, options) = match request.prepare() {
+            Ok(prepared) => prepared,
+            Err(e) => return Err(error_response(&request.id, e)),
+        };
+        if swaps > self.max_swaps {
+            return Err(error_response(
+                &request.id,
+                format!(
+                    "Too many swaps ({swaps}), server allows up to {}",
+                    self.max_swaps
+                ),
+            ));
+        }
+        let clock = Instant::now();
+        let deadline = self.time_limit.map(|x| clock + x);
+        let respond = |words: &[Word], partial: bool, timed_out: bool| {
+            let timings = Timings {
+                dict: self.dictionary_info.load_ms,
+                solver: clock.elapsed().as_secs_f64() * 1000.,
+            };
+            let response = Response {
+                id: &request.id,
+                partial,
+                timed_out,
+                output: JsonOutput::new(&board, words, &timings, &options),
+            };
+            serde_json::to_string(&response).map_err(|e| error_response(&request.id, e.to_string()))
+        };
+        let progress = |words: &[Word]| {
+            if let (Some(partial), Ok(response)) = (partial, respond(words, true, false)) {
+                partial(response);
+            }
+        };
+        let words = solver_wrapper(
+            &board,
+            swaps,
+            self.threads,
+            self.dictionary,
+            request.count,
+            &options,
+            SolveControl {
+                cancel,
+                progress: partial.is_some().then_some(&progress),
+                pool: self.pool.as_ref(),
+                deadline,
+            },
+        );
+        let timed_out = deadline.is_some_and(|x| Instant::now() >= x);
+        respond(&words, false, timed_out)
+    }
+}
+
+/// Serialises [ErrorResponse].
+fn error_response(id: &Value, error: String) -> String {
+    serde_json::to_string(&ErrorResponse { id, error })
+        .unwrap_or_else(|_| String::from(r#"{"error":"Failed to serialise error"}"#))
+}
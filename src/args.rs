@@ -1,6 +1,13 @@
 use argh::{FromArgValue, FromArgs};
 
-use crate::{output::OutputFormat, spellcast::Board};
+use std::{str::FromStr, time::Duration};
+
+use spellcast_solver::{
+    output::OutputFormat,
+    random::LetterDistribution,
+    spellcast::{Adjacency, Board, WordMultiplierRule},
+    utils::MAX_SERVER_SWAPS,
+};
 
 #[derive(FromArgs, Debug)]
 /// Spellcast solver CLI.
@@ -20,8 +27,12 @@ pub struct Args {
         default = "1"
     )]
     pub threads: u8,
-    #[argh(option, description = "board string", short = 'b')]
-    pub board: Board,
+    #[argh(
+        option,
+        description = "board string, JSON board, @file or - for stdin",
+        short = 'b'
+    )]
+    pub board: Option<BoardInput>,
     #[argh(
         option,
         description = "number of top moves to show (def=5)",
@@ -31,28 +42,271 @@ pub struct Args {
     pub move_count: u8,
     #[argh(
         option,
-        description = "number of swaps to consider (def=0)",
-        short = 's',
-        default = "0"
+        description = "number of swaps to consider (def=0 or from JSON board)",
+        short = 's'
+    )]
+    pub swaps: Option<u8>,
+    #[argh(
+        option,
+        description = "time limit in seconds, 0 for none (def=none, 10 for servers)",
+        from_str_fn(parse_time_limit)
+    )]
+    pub time_limit: Option<Duration>,
+    #[argh(
+        option,
+        description = "maximum swaps server requests can ask for (def=8)",
+        default = "MAX_SERVER_SWAPS"
     )]
-    pub swaps: u8,
+    pub max_swaps: u8,
     #[argh(
         option,
         description = "output format (def=simple)",
+        from_str_fn(parse_format),
         short = 'f',
         default = "OutputFormat::Simple"
     )]
     pub format: OutputFormat,
+    #[argh(
+        option,
+        description = "adjacency rule (def=8way)",
+        from_str_fn(parse_adjacency),
+        short = 'a',
+        default = "Adjacency::default()"
+    )]
+    pub adjacency: Adjacency,
+    #[argh(option, description = "tiles that must be used, e.g. A1,C3")]
+    pub include: Option<String>,
+    #[argh(option, description = "tiles that must not be used, e.g. A1,C3")]
+    pub exclude: Option<String>,
+    #[argh(option, description = "tiles word can start on, e.g. A1,C3")]
+    pub start: Option<String>,
+    #[argh(option, description = "tiles word can end on, e.g. A1,C3")]
+    pub end: Option<String>,
+    #[argh(option, description = "minimum word length")]
+    pub min_length: Option<u8>,
+    #[argh(option, description = "maximum word length")]
+    pub max_length: Option<u8>,
+    #[argh(option, description = "pattern words must match, e.g. *qu* or *ing")]
+    pub pattern: Option<String>,
+    #[argh(option, description = "regex words must match")]
+    pub regex: Option<String>,
+    #[argh(option, description = "file with list of words that can be used")]
+    pub word_list: Option<String>,
+    #[argh(
+        option,
+        description = "how word multipliers stack (def=max)",
+        default = "WordMultiplierRule::default()"
+    )]
+    pub multiplier_rule: WordMultiplierRule,
+    #[argh(switch, description = "print parsed board before solving")]
+    pub show_board: bool,
+    #[argh(switch, description = "report every problem in board string")]
+    pub strict: bool,
+    #[argh(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(FromArgs, Debug)]
+#[argh(subcommand)]
+pub enum Command {
+    Serve(ServeArgs),
+    Http(HttpArgs),
+    Ws(WsArgs),
+    Batch(BatchArgs),
+    Bench(BenchArgs),
+    Verify(VerifyArgs),
+}
+
+#[derive(FromArgs, Debug)]
+/// Keep the dictionary loaded and solve boards from JSON requests.
+#[argh(subcommand, name = "serve")]
+pub struct ServeArgs {
+    #[argh(
+        option,
+        description = "unix socket to listen on instead of stdin/stdout"
+    )]
+    pub socket: Option<String>,
+}
+
+#[derive(FromArgs, Debug)]
+/// Keep the dictionary loaded and solve boards from HTTP requests.
+#[argh(subcommand, name = "http")]
+pub struct HttpArgs {
+    #[argh(
+        option,
+        description = "address to listen on (def=127.0.0.1:8080)",
+        default = "\"127.0.0.1:8080\".into()"
+    )]
+    pub address: String,
+    #[argh(
+        option,
+        description = "origin allowed by CORS, can be repeated (* for any)"
+    )]
+    pub cors: Vec<String>,
 }
 
-impl FromArgValue for OutputFormat {
+#[derive(FromArgs, Debug)]
+/// Keep the dictionary loaded and stream results of boards sent over WebSocket.
+#[argh(subcommand, name = "ws")]
+pub struct WsArgs {
+    #[argh(
+        option,
+        description = "address to listen on (def=127.0.0.1:8081)",
+        default = "\"127.0.0.1:8081\".into()"
+    )]
+    pub address: String,
+}
+
+#[derive(FromArgs, Debug)]
+/// Solve many boards (one per line) with one loaded dictionary and print NDJSON results.
+#[argh(subcommand, name = "batch")]
+pub struct BatchArgs {
+    #[argh(
+        positional,
+        description = "file with boards or - for stdin (def=-)",
+        default = "\"-\".into()"
+    )]
+    pub input: String,
+}
+
+#[derive(FromArgs, Debug)]
+/// Benchmark the solver on seeded random boards.
+#[argh(subcommand, name = "bench")]
+pub struct BenchArgs {
+    #[argh(
+        option,
+        description = "number of boards to solve for each benchmark (def=100)",
+        from_str_fn(parse_board_count),
+        default = "100"
+    )]
+    pub boards: usize,
+    #[argh(option, description = "seed of random boards (def=0)", default = "0")]
+    pub seed: u64,
+    #[argh(option, description = "board width and height (def=5)", default = "5")]
+    pub size: usize,
+    #[argh(
+        option,
+        description = "letter distribution: uniform, english or a=8,b=2,... (def=uniform)",
+        default = "LetterDistribution::default()"
+    )]
+    pub letters: LetterDistribution,
+    #[argh(
+        option,
+        description = "swap counts to benchmark (def=0,1,2,3)",
+        default = "CountList(vec![0, 1, 2, 3])"
+    )]
+    pub swaps: CountList,
+    #[argh(
+        option,
+        description = "thread counts to benchmark (def=1)",
+        default = "CountList(vec![1])"
+    )]
+    pub threads: CountList,
+    #[argh(
+        option,
+        description = "also write results as JSON to file (- for stdout)"
+    )]
+    pub json: Option<String>,
+}
+
+#[derive(FromArgs, Debug)]
+/// Check that a move (JSON steps) is valid on the board and recompute its score.
+#[argh(subcommand, name = "verify")]
+pub struct VerifyArgs {
+    #[argh(
+        positional,
+        description = "steps as JSON array or word object, @file or - for stdin (def=-)",
+        default = "\"-\".into()"
+    )]
+    pub steps: String,
+}
+
+/// Comma-separated list of counts, e.g. `0,1,2,3`.
+#[derive(Debug)]
+pub struct CountList(pub Vec<u8>);
+
+impl FromStr for CountList {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(|x| {
+                x.trim()
+                    .parse::<u8>()
+                    .map_err(|_| format!("Invalid count {x}"))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(CountList)
+    }
+}
+
+/// Board argument.
+/// It can be board string or JSON board (see [Board::from_json]) itself, `@file` to read it from file or `-` to read it from stdin.
+/// Only the content is read during argument parsing, it is parsed with [BoardInput::parse] once all other arguments (e.g. `--strict`) are known.
+#[derive(Debug)]
+pub struct BoardInput(pub String);
+
+impl BoardInput {
+    /// Parses board with [Board::parse_input].
+    pub fn parse(&self, strict: bool) -> Result<(Board, Option<u8>), String> {
+        Board::parse_input(&self.0, strict)
+    }
+}
+
+/// Reads argument value that can also be `@file` or `-` for stdin, `name` is used in errors.
+pub fn read_value(value: &str, name: &str) -> Result<String, String> {
+    if value == "-" {
+        std::io::read_to_string(std::io::stdin())
+            .map_err(|e| format!("Failed to read {name} from stdin: {e}"))
+    } else if let Some(path) = value.strip_prefix('@') {
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read {name} file: {e}"))
+    } else {
+        Ok(value.to_owned())
+    }
+}
+
+impl FromArgValue for BoardInput {
     fn from_arg_value(value: &str) -> Result<Self, String> {
-        match value {
-            "board" => Ok(Self::Board),
-            "json" => Ok(Self::JSON),
-            "simple" => Ok(Self::Simple),
-            _ => Err(String::from("Expected board/json/simple")),
-        }
+        read_value(value, "board").map(BoardInput)
+    }
+}
+
+/// Parses number of benchmark boards, which has to be positive to have any times to report.
+fn parse_board_count(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(0) | Err(_) => Err(String::from("Expected positive number of boards")),
+        Ok(count) => Ok(count),
+    }
+}
+
+/// Parses time limit in seconds (fractions are allowed).
+fn parse_time_limit(value: &str) -> Result<Duration, String> {
+    value
+        .parse()
+        .ok()
+        .and_then(|x| Duration::try_from_secs_f64(x).ok())
+        .ok_or_else(|| String::from("Expected number of seconds"))
+}
+
+/// Parses output format name.
+fn parse_format(value: &str) -> Result<OutputFormat, String> {
+    match value {
+        "board" => Ok(OutputFormat::Board),
+        "json" => Ok(OutputFormat::Json),
+        "simple" => Ok(OutputFormat::Simple),
+        _ => Err(String::from("Expected board/json/simple")),
+    }
+}
+
+/// Parses adjacency rule name or `@file` with adjacency list.
+fn parse_adjacency(value: &str) -> Result<Adjacency, String> {
+    match value.strip_prefix('@') {
+        Some(path) => Adjacency::parse_list(
+            &std::fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read adjacency file: {e}"))?,
+        ),
+        None => Adjacency::from_name(value)
+            .map_err(|_| String::from("Expected 8way/4way/8way-wrap/4way-wrap/@file")),
     }
 }
 
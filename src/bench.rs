@@ -0,0 +1,169 @@
+//! Built-in benchmark (`bench` subcommand) on seeded random boards.
+//! It replaces external benchmark.py, which also measured process startup and dictionary loading for every board.
+
+use std::{fmt, io::Write, time::Instant};
+
+use spellcast_solver::{
+    dictionary::Node,
+    random::generate_boards,
+    spellcast::{solver_wrapper, SolveControl, SolveOptions},
+};
+
+use crate::args::BenchArgs;
+
+/// Summary of measured times (in milliseconds).
+#[derive(serde::Serialize)]
+pub struct Stats {
+    pub min: f64,
+    pub avg: f64,
+    pub max: f64,
+    /// Mean absolute deviation from `avg`, same as in benchmark.py.
+    pub mdev: f64,
+}
+
+impl Stats {
+    pub fn new(times: &[f64]) -> Stats {
+        let count = times.len().max(1) as f64;
+        let avg = times.iter().sum::<f64>() / count;
+        Stats {
+            min: times.iter().copied().fold(f64::INFINITY, f64::min),
+            avg,
+            max: times.iter().copied().fold(0., f64::max),
+            mdev: times.iter().map(|x| (x - avg).abs()).sum::<f64>() / count,
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.1}/{:.1}/{:.1}/{:.1}",
+            self.min, self.avg, self.max, self.mdev
+        )
+    }
+}
+
+/// Result of single benchmark (thread count + swap count combination).
+#[derive(serde::Serialize)]
+pub struct BenchResult {
+    pub threads: u8,
+    pub swaps: u8,
+    #[serde(rename = "solver_ms")]
+    pub stats: Stats,
+}
+
+/// Whole benchmark report, written with `--json`.
+#[derive(serde::Serialize)]
+pub struct BenchReport {
+    pub seed: u64,
+    pub boards: usize,
+    pub size: usize,
+    pub letters: String,
+    pub dict_ms: f64,
+    pub results: Vec<BenchResult>,
+}
+
+/// Runs the benchmark and writes min/avg/max/mdev of every thread count and swap count to `out` as it goes.
+/// Every combination solves the same boards, so results are directly comparable.
+pub fn run(
+    args: &BenchArgs,
+    dictionary: &Vec<(char, Node)>,
+    dict_ms: f64,
+    out: &mut dyn Write,
+) -> Result<BenchReport, String> {
+    let boards = generate_boards(args.seed, args.boards, args.size, &args.letters)
+        .map_err(|e| format!("Invalid board size: {e}"))?;
+    let write_error = |e: std::io::Error| format!("Failed to write results: {e}");
+    writeln!(
+        out,
+        "Running benchmark for {} {}x{} boards (seed {}, {} letters)",
+        boards.len(),
+        args.size,
+        args.size,
+        args.seed,
+        args.letters
+    )
+    .map_err(write_error)?;
+    let options = SolveOptions::default();
+    let mut results = vec![];
+    for threads in &args.threads.0 {
+        writeln!(
+            out,
+            "[{threads} thread{}] min/avg/max/mdev",
+            if *threads == 1 { "" } else { "s" }
+        )
+        .map_err(write_error)?;
+        for swaps in &args.swaps.0 {
+            let times: Vec<f64> = boards
+                .iter()
+                .map(|board| {
+                    let clock = Instant::now();
+                    solver_wrapper(
+                        board,
+                        *swaps,
+                        *threads,
+                        dictionary,
+                        5,
+                        &options,
+                        SolveControl::default(),
+                    );
+                    clock.elapsed().as_secs_f64() * 1000.
+                })
+                .collect();
+            let stats = Stats::new(&times);
+            writeln!(out, "[{swaps} swaps] {stats}").map_err(write_error)?;
+            results.push(BenchResult {
+                threads: *threads,
+                swaps: *swaps,
+                stats,
+            });
+        }
+    }
+    writeln!(out, "[DICT] {dict_ms:.1}").map_err(write_error)?;
+    Ok(BenchReport {
+        seed: args.seed,
+        boards: boards.len(),
+        size: args.size,
+        letters: args.letters.to_string(),
+        dict_ms,
+        results,
+    })
+}
+
+/// Formats average times as markdown table, same as the one in README.md.
+pub fn markdown_table(report: &BenchReport) -> String {
+    let plural = |count: u8| if count == 1 { "" } else { "s" };
+    let mut threads: Vec<u8> = report.results.iter().map(|x| x.threads).collect();
+    let mut swaps: Vec<u8> = report.results.iter().map(|x| x.swaps).collect();
+    threads.dedup();
+    swaps.sort_unstable();
+    swaps.dedup();
+    let mut table = format!(
+        "| Benchmark | {} |\n| --- |{}\n",
+        swaps
+            .iter()
+            .map(|x| format!("{x} swap{}", plural(*x)))
+            .collect::<Vec<_>>()
+            .join(" | "),
+        " --- |".repeat(swaps.len())
+    );
+    for thread_count in threads {
+        let cells: Vec<String> = swaps
+            .iter()
+            .map(|swap_count| {
+                report
+                    .results
+                    .iter()
+                    .find(|x| x.threads == thread_count && x.swaps == *swap_count)
+                    .map_or(String::from("-"), |x| format!("{:.1} ms", x.stats.avg))
+            })
+            .collect();
+        table.push_str(&format!(
+            "| {thread_count} thread{} | {} |\n",
+            plural(thread_count),
+            cells.join(" | ")
+        ));
+    }
+    table
+}
@@ -28,19 +28,22 @@ impl Node {
 
 /// Parses string of words separated by newlines into tree-like structure.
 /// Because of how dictionary works, words shorter than 3 characters are ignored.
-/// Words longer than 25 characters are also ignored, because it is impossible to play them in Spellcast.
+/// Long words are kept: the solver never makes words longer than number of tiles on the board, so board size is what limits their length.
 /// Despite code having lot of things that in theory can panic, it does not panic under normal circumstances.
 /// Each `Both`/`Prefix` node is guaranteed to have at least one child node, and each branch is guaranteed to eventually end in `Word` node.
 pub fn load_dictionary_tree(string: String) -> Vec<(char, Node)> {
     let mut root = Node::Prefix {
         next_letters: vec![],
     };
-    for word in string.lines().filter(|x| x.len() >= 3 && x.len() <= 25) {
+    for word in string.lines() {
+        let letters: Vec<char> = word.chars().collect();
+        if letters.len() < 3 {
+            continue;
+        }
         let mut parent = &mut root;
-        for i in 0..(word.len() - 1) {
-            let letter = word.chars().nth(i).unwrap();
-            let next_letter = word.chars().nth(i + 1).unwrap();
-            let is_next_letter_last_letter = i + 1 == word.len() - 1;
+        for (i, pair) in letters.windows(2).enumerate() {
+            let (letter, next_letter) = (pair[0], pair[1]);
+            let is_next_letter_last_letter = i + 1 == letters.len() - 1;
             let next_letter_node_def = (
                 next_letter,
                 if is_next_letter_last_letter {
@@ -65,9 +68,17 @@ pub fn load_dictionary_tree(string: String) -> Vec<(char, Node)> {
                 match current_letter_node {
                     Node::Both { .. } | Node::Prefix { .. } => {
                         let mut next_found = false;
-                        for (sub_letter, _) in current_letter_node.get_next_letters() {
+                        for (sub_letter, sub_node) in current_letter_node.get_next_letters() {
                             if *sub_letter == next_letter {
                                 next_found = true;
+                                // Word can be a prefix of longer word that was added before it (if dictionary isn't sorted).
+                                if let (true, Node::Prefix { next_letters }) =
+                                    (is_next_letter_last_letter, &mut *sub_node)
+                                {
+                                    *sub_node = Node::Both {
+                                        next_letters: std::mem::take(next_letters),
+                                    };
+                                }
                                 break;
                             }
                         }
@@ -102,6 +113,86 @@ pub fn load_dictionary_tree(string: String) -> Vec<(char, Node)> {
     }
 }
 
+/// Creates copy of dictionary tree with only words for which `keep` returns true.
+/// Branches without any kept words are removed entirely, so [crate::spellcast::solver] never has to visit them.
+/// Same guarantees as in [load_dictionary_tree] apply to the returned tree.
+pub fn filter_dictionary_tree(
+    dictionary: &[(char, Node)],
+    keep: &dyn Fn(&str) -> bool,
+) -> Vec<(char, Node)> {
+    fn filter_letters(
+        next_letters: &[(char, Node)],
+        prefix: &mut String,
+        keep: &dyn Fn(&str) -> bool,
+    ) -> Vec<(char, Node)> {
+        let mut filtered = vec![];
+        for (letter, node) in next_letters {
+            prefix.push(*letter);
+            let (is_word, next_letters) = match node {
+                Node::Prefix { next_letters } => {
+                    (false, filter_letters(next_letters, prefix, keep))
+                }
+                Node::Word => (keep(prefix), vec![]),
+                Node::Both { next_letters } => {
+                    (keep(prefix), filter_letters(next_letters, prefix, keep))
+                }
+            };
+            prefix.pop();
+            match (is_word, next_letters.is_empty()) {
+                (true, true) => filtered.push((*letter, Node::Word)),
+                (true, false) => filtered.push((*letter, Node::Both { next_letters })),
+                (false, false) => filtered.push((*letter, Node::Prefix { next_letters })),
+                (false, true) => {}
+            }
+        }
+        filtered
+    }
+    filter_letters(dictionary, &mut String::new(), keep)
+}
+
+/// Returns number of words in dictionary tree.
+pub fn count_words(dictionary: &[(char, Node)]) -> usize {
+    dictionary
+        .iter()
+        .map(|(_, node)| match node {
+            Node::Prefix { next_letters } => count_words(next_letters),
+            Node::Word => 1,
+            Node::Both { next_letters } => 1 + count_words(next_letters),
+        })
+        .sum()
+}
+
+/// Returns whether dictionary tree contains `word`.
+pub fn contains_word(dictionary: &[(char, Node)], word: &str) -> bool {
+    let mut next_letters = dictionary;
+    let mut letters = word.chars().peekable();
+    while let Some(letter) = letters.next() {
+        let Some((_, node)) = next_letters.iter().find(|(x, _)| *x == letter) else {
+            return false;
+        };
+        match (node, letters.peek()) {
+            (Node::Word | Node::Both { .. }, None) => return true,
+            (Node::Prefix { next_letters: next } | Node::Both { next_letters: next }, Some(_)) => {
+                next_letters = next
+            }
+            _ => return false,
+        }
+    }
+    false
+}
+
+/// Loads dictionary from bytes of either dictionary file or its binary cache (`.bin` file next to it), e.g. in browser where there are no files.
+/// Binary cache starts with length of the tree as u64, so it is recognised by NUL bytes, which never appear in dictionary files.
+pub fn load_dictionary_bytes(bytes: &[u8]) -> Result<Vec<(char, Node)>, String> {
+    if bytes.iter().take(8).any(|x| *x == 0) {
+        bincode::deserialize(bytes).map_err(|e| format!("Invalid binary dictionary: {e}"))
+    } else {
+        String::from_utf8(bytes.to_vec())
+            .map(load_dictionary_tree)
+            .map_err(|e| format!("Dictionary is not valid UTF-8: {e}"))
+    }
+}
+
 /// Loads dictionary from file.
 /// Basically a wrapper for [load_dictionary_tree] that handles file access.
 pub fn load_dictionary_file(path: &String) -> Result<Vec<(char, Node)>, String> {
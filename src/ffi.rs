@@ -0,0 +1,381 @@
+//! C ABI, so the solver can be used from other languages without spawning a process.
+//! Header is generated with `cargo build --release --features header` into include/spellcast_solver.h.
+//! Dictionaries, boards and results are opaque handles that must be freed with matching `*_free` function.
+//! Functions that fail return NULL (or false) and store the error, which can be read with [spellcast_last_error].
+//! Panics can't unwind into C, so they are caught and reported the same way.
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CStr, CString},
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr,
+    time::Instant,
+};
+
+use crate::{
+    dictionary::{load_dictionary_file, Node},
+    output::{JsonOutput, Timings},
+    spellcast::{
+        compile_regex, pattern_to_regex, solver_wrapper, Adjacency, Board, SolveControl,
+        SolveOptions, Word, WordMultiplierRule,
+    },
+};
+
+thread_local! {
+    /// Error of the last failed call on this thread.
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Stores error for [spellcast_last_error].
+fn set_error(error: String) {
+    // Error messages don't contain NUL bytes, but replace them just in case.
+    let error = CString::new(error.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|x| *x.borrow_mut() = Some(error));
+}
+
+/// Stores error for [spellcast_last_error] and returns NULL, so it can be used as return value directly.
+fn fail<T>(error: String) -> *mut T {
+    set_error(error);
+    ptr::null_mut()
+}
+
+/// Runs body of exported function, so panic inside it is stored as error and `on_panic` is returned instead of unwinding into C.
+fn guard<T>(on_panic: T, body: impl FnOnce() -> T) -> T {
+    catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|panic| {
+        let message = match (panic.downcast_ref::<&str>(), panic.downcast_ref::<String>()) {
+            (Some(message), _) => message,
+            (_, Some(message)) => message.as_str(),
+            _ => "unknown panic",
+        };
+        set_error(format!("Internal error: {message}"));
+        on_panic
+    })
+}
+
+/// Reads optional C string, NULL is `None`.
+///
+/// # Safety
+/// `string` must be NULL or valid NUL-terminated string.
+unsafe fn read_string(string: *const c_char, name: &str) -> Result<Option<String>, String> {
+    if string.is_null() {
+        return Ok(None);
+    }
+    CStr::from_ptr(string)
+        .to_str()
+        .map(|x| Some(x.to_owned()))
+        .map_err(|_| format!("{name} is not valid UTF-8"))
+}
+
+/// Dictionary loaded with [spellcast_dictionary_load].
+pub struct SpellcastDictionary(Vec<(char, Node)>);
+
+/// Board parsed with [spellcast_board_parse].
+pub struct SpellcastBoard(Board);
+
+/// Results of [spellcast_solve].
+pub struct SpellcastResults {
+    words: Vec<Word>,
+    /// Word strings and tile indices, kept here so pointers in [SpellcastWord] stay valid.
+    strings: Vec<CString>,
+    tiles: Vec<Vec<u16>>,
+    /// Same JSON as `-f json` output.
+    json: CString,
+}
+
+/// Solve options, mirroring CLI arguments (see CLI.md).
+/// Use [spellcast_options_default] to get defaults and change only what you need.
+#[repr(C)]
+pub struct SpellcastOptions {
+    /// Number of swaps to consider.
+    pub swaps: u8,
+    /// Number of top moves to return.
+    pub move_count: u8,
+    /// Number of threads to use.
+    pub threads: u8,
+    /// Minimum word length.
+    pub min_length: u8,
+    /// Maximum word length, 0 for no limit.
+    pub max_length: u8,
+    /// Tiles that must be used, e.g. `A1,C3` (NULL for none).
+    pub include: *const c_char,
+    /// Tiles that must not be used (NULL for none).
+    pub exclude: *const c_char,
+    /// Tiles word can start on (NULL for any).
+    pub start: *const c_char,
+    /// Tiles word can end on (NULL for any).
+    pub end: *const c_char,
+    /// Pattern words must match, e.g. `*qu*` (NULL for none).
+    pub pattern: *const c_char,
+    /// Regex words must match (NULL for none).
+    pub regex: *const c_char,
+    /// Adjacency rule name, e.g. `4way` (NULL for 8way).
+    pub adjacency: *const c_char,
+    /// How word multipliers stack, `max` or `multiply` (NULL for max).
+    pub multiplier_rule: *const c_char,
+}
+
+/// Single word of [SpellcastResults], pointers are valid until results are freed.
+#[repr(C)]
+pub struct SpellcastWord {
+    /// Word itself (lowercase).
+    pub word: *const c_char,
+    pub score: u16,
+    pub gems_collected: u8,
+    pub swaps_used: u8,
+    /// Tile indices of the path (row by row from top left), `tile_count` items.
+    pub tiles: *const u16,
+    pub tile_count: usize,
+}
+
+/// Returns error message of the last failed call on this thread (NULL if there is none).
+/// It is valid until next failed call on the same thread.
+#[no_mangle]
+pub extern "C" fn spellcast_last_error() -> *const c_char {
+    LAST_ERROR.with(|x| x.borrow().as_ref().map_or(ptr::null(), |x| x.as_ptr()))
+}
+
+/// Loads dictionary file (same as `-d`), returns NULL on failure.
+///
+/// # Safety
+/// `path` must be valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn spellcast_dictionary_load(
+    path: *const c_char,
+) -> *mut SpellcastDictionary {
+    guard(ptr::null_mut(), || {
+        let path = match read_string(path, "Path") {
+            Ok(Some(path)) => path,
+            Ok(None) => return fail(String::from("Path is NULL")),
+            Err(e) => return fail(e),
+        };
+        match load_dictionary_file(&path) {
+            Ok(dictionary) => Box::into_raw(Box::new(SpellcastDictionary(dictionary))),
+            Err(e) => fail(format!("Failed to load dictionary: {e}")),
+        }
+    })
+}
+
+/// Frees dictionary, NULL is ignored.
+///
+/// # Safety
+/// `dictionary` must be NULL or returned by [spellcast_dictionary_load] and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn spellcast_dictionary_free(dictionary: *mut SpellcastDictionary) {
+    guard((), || {
+        if !dictionary.is_null() {
+            drop(Box::from_raw(dictionary));
+        }
+    })
+}
+
+/// Parses board string (same as `-b`), returns NULL on failure.
+///
+/// # Safety
+/// `board` must be valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn spellcast_board_parse(board: *const c_char) -> *mut SpellcastBoard {
+    guard(ptr::null_mut(), || {
+        let board = match read_string(board, "Board") {
+            Ok(Some(board)) => board,
+            Ok(None) => return fail(String::from("Board is NULL")),
+            Err(e) => return fail(e),
+        };
+        match board.parse::<Board>() {
+            Ok(board) => Box::into_raw(Box::new(SpellcastBoard(board))),
+            Err(e) => fail(format!("Invalid board: {e}")),
+        }
+    })
+}
+
+/// Frees board, NULL is ignored.
+///
+/// # Safety
+/// `board` must be NULL or returned by [spellcast_board_parse] and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn spellcast_board_free(board: *mut SpellcastBoard) {
+    guard((), || {
+        if !board.is_null() {
+            drop(Box::from_raw(board));
+        }
+    })
+}
+
+/// Returns default options (same as CLI defaults).
+#[no_mangle]
+pub extern "C" fn spellcast_options_default() -> SpellcastOptions {
+    SpellcastOptions {
+        swaps: 0,
+        move_count: 5,
+        threads: 1,
+        min_length: 0,
+        max_length: 0,
+        include: ptr::null(),
+        exclude: ptr::null(),
+        start: ptr::null(),
+        end: ptr::null(),
+        pattern: ptr::null(),
+        regex: ptr::null(),
+        adjacency: ptr::null(),
+        multiplier_rule: ptr::null(),
+    }
+}
+
+/// Applies options to copy of the board and converts them to [SolveOptions].
+///
+/// # Safety
+/// All strings in `options` must be NULL or valid NUL-terminated strings.
+unsafe fn prepare(
+    board: &Board,
+    options: &SpellcastOptions,
+) -> Result<(Board, SolveOptions), String> {
+    let mut board = board.clone();
+    if let Some(adjacency) = read_string(options.adjacency, "Adjacency")? {
+        board.set_adjacency(&Adjacency::from_name(&adjacency)?)?;
+    }
+    if let Some(rule) = read_string(options.multiplier_rule, "Multiplier rule")? {
+        board.set_word_multiplier_rule(rule.parse::<WordMultiplierRule>()?);
+    }
+    let tiles = |value: *const c_char, name: &str| match read_string(value, name)? {
+        Some(value) => board.parse_tiles(&value),
+        None => Ok(vec![]),
+    };
+    let mut patterns = vec![];
+    if let Some(pattern) = read_string(options.pattern, "Pattern")? {
+        patterns.push(pattern_to_regex(&pattern)?);
+    }
+    if let Some(regex) = read_string(options.regex, "Regex")? {
+        patterns.push(compile_regex(&regex)?);
+    }
+    let solve_options = SolveOptions {
+        include: tiles(options.include, "Include")?,
+        exclude: tiles(options.exclude, "Exclude")?,
+        start: tiles(options.start, "Start")?,
+        end: tiles(options.end, "End")?,
+        min_length: options.min_length,
+        max_length: (options.max_length > 0).then_some(options.max_length),
+        patterns,
+        word_list: None,
+    };
+    Ok((board, solve_options))
+}
+
+/// Solves the board, returns NULL on failure.
+/// `options` can be NULL to use defaults.
+///
+/// # Safety
+/// `dictionary` and `board` must be valid handles, `options` must be NULL or point to valid options.
+#[no_mangle]
+pub unsafe extern "C" fn spellcast_solve(
+    dictionary: *const SpellcastDictionary,
+    board: *const SpellcastBoard,
+    options: *const SpellcastOptions,
+) -> *mut SpellcastResults {
+    guard(ptr::null_mut(), || {
+        if dictionary.is_null() || board.is_null() {
+            return fail(String::from("Dictionary and board can't be NULL"));
+        }
+        let default_options = spellcast_options_default();
+        let options = options.as_ref().unwrap_or(&default_options);
+        let (board, solve_options) = match prepare(&(*board).0, options) {
+            Ok(prepared) => prepared,
+            Err(e) => return fail(e),
+        };
+        let clock = Instant::now();
+        let words = solver_wrapper(
+            &board,
+            options.swaps,
+            options.threads,
+            &(*dictionary).0,
+            options.move_count,
+            &solve_options,
+            SolveControl::default(),
+        );
+        let timings = Timings {
+            dict: 0.,
+            solver: clock.elapsed().as_secs_f64() * 1000.,
+        };
+        let json =
+            match serde_json::to_string(&JsonOutput::new(&board, &words, &timings, &solve_options))
+            {
+                Ok(json) => CString::new(json).unwrap_or_default(),
+                Err(e) => return fail(format!("Failed to serialise results: {e}")),
+            };
+        Box::into_raw(Box::new(SpellcastResults {
+            strings: words
+                .iter()
+                .map(|x| CString::new(x.word(&board, false)).unwrap_or_default())
+                .collect(),
+            tiles: words
+                .iter()
+                .map(|x| x.steps.iter().map(|step| step.index()).collect())
+                .collect(),
+            words,
+            json,
+        }))
+    })
+}
+
+/// Returns number of words in results.
+///
+/// # Safety
+/// `results` must be valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn spellcast_results_len(results: *const SpellcastResults) -> usize {
+    guard(0, || results.as_ref().map_or(0, |x| x.words.len()))
+}
+
+/// Writes word at `index` (best word first) to `word`, returns false if index is out of range.
+///
+/// # Safety
+/// `results` must be valid handle and `word` must point to writable [SpellcastWord].
+#[no_mangle]
+pub unsafe extern "C" fn spellcast_results_get(
+    results: *const SpellcastResults,
+    index: usize,
+    word: *mut SpellcastWord,
+) -> bool {
+    guard(false, || {
+        let Some(results) = results.as_ref() else {
+            return false;
+        };
+        let Some(found) = results.words.get(index) else {
+            return false;
+        };
+        if word.is_null() {
+            return false;
+        }
+        *word = SpellcastWord {
+            word: results.strings[index].as_ptr(),
+            score: found.score,
+            gems_collected: found.gems_collected,
+            swaps_used: found.swaps_used,
+            tiles: results.tiles[index].as_ptr(),
+            tile_count: results.tiles[index].len(),
+        };
+        true
+    })
+}
+
+/// Returns results as JSON, same as `-f json` output (see CLI.md), valid until results are freed.
+///
+/// # Safety
+/// `results` must be valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn spellcast_results_json(results: *const SpellcastResults) -> *const c_char {
+    guard(ptr::null(), || {
+        results.as_ref().map_or(ptr::null(), |x| x.json.as_ptr())
+    })
+}
+
+/// Frees results, NULL is ignored.
+///
+/// # Safety
+/// `results` must be NULL or returned by [spellcast_solve] and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn spellcast_results_free(results: *mut SpellcastResults) {
+    guard((), || {
+        if !results.is_null() {
+            drop(Box::from_raw(results));
+        }
+    })
+}
@@ -0,0 +1,16 @@
+//! Spellcast solver library.
+//! It is used by the CLI (src/main.rs) and exposed to other languages through [ffi], Python module (`python` feature) and JavaScript API (`wasm` feature).
+
+pub mod api;
+pub mod dictionary;
+pub mod ffi;
+pub mod output;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod random;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod server;
+pub mod spellcast;
+pub mod utils;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -1,33 +1,200 @@
+#[cfg(not(target_arch = "wasm32"))]
+use spellcast_solver::{api, server};
+use std::{
+    collections::HashSet,
+    io::{BufRead, Write},
+};
+
+use regex::Regex;
+use spellcast_solver::{
+    dictionary,
+    dictionary::Node,
+    output, quit, spellcast,
+    utils::{RESET, YELLOW},
+};
+
 mod args;
-mod dictionary;
-mod output;
-mod spellcast;
-mod utils;
+mod bench;
 
 fn main() {
     let args = args::parse();
-    if args.swaps > 3 {
-        quit!("Swap count can't be higher than 3!")
+    match &args.command {
+        Some(args::Command::Batch(batch_args)) => batch(&args, batch_args),
+        Some(args::Command::Bench(bench_args)) => bench(&args, bench_args),
+        Some(args::Command::Verify(verify_args)) => verify(&args, verify_args),
+        Some(command) => serve(&args, command),
+        None => solve(&args),
+    }
+}
+
+/// Runs server subcommand.
+#[cfg(not(target_arch = "wasm32"))]
+fn serve(args: &args::Args, command: &args::Command) {
+    let solver = server_solver(args);
+    let result = match command {
+        args::Command::Serve(serve_args) => match &serve_args.socket {
+            Some(path) => server::serve_socket(&solver, path),
+            None => server::serve_stdio(&solver),
+        },
+        args::Command::Http(http_args) => std::net::TcpListener::bind(&http_args.address)
+            .and_then(|listener| server::serve_http(&solver, listener, &http_args.cors)),
+        args::Command::Ws(ws_args) => std::net::TcpListener::bind(&ws_args.address)
+            .and_then(|listener| server::serve_websocket(&solver, listener)),
+        args::Command::Batch(_) | args::Command::Bench(_) | args::Command::Verify(_) => {
+            unreachable!("not a server")
+        }
+    };
+    if let Err(e) = result {
+        quit!("Server failed: {e}");
     }
+}
+
+/// Servers need networking, which isn't available in wasm32 builds.
+#[cfg(target_arch = "wasm32")]
+fn serve(_args: &args::Args, _command: &args::Command) {
+    quit!("Servers are not supported on this platform");
+}
+
+/// Loads dictionary (leaking it, so it can be used by threads) and returns it together with time spent loading it (in milliseconds).
+fn load_dictionary(path: &String) -> (&'static Vec<(char, Node)>, f64) {
     let clock = std::time::Instant::now();
     // TODO: Maybe make dictionary loading a part of argument parsing (similar to board argument)?
-    let dictionary = match dictionary::load_dictionary_file(&args.dictionary) {
+    let dictionary = match dictionary::load_dictionary_file(path) {
         Ok(dictionary) => Box::leak(Box::new(dictionary)),
         Err(e) => quit!("Failed to load dictionary: {e}"),
     };
-    let elapsed_dict = clock.elapsed().as_secs_f64() * 1000.;
+    (dictionary, clock.elapsed().as_secs_f64() * 1000.)
+}
+
+/// Loads dictionary and creates solver for server subcommands.
+#[cfg(not(target_arch = "wasm32"))]
+fn server_solver(args: &args::Args) -> api::Solver {
+    let (dictionary, load_ms) = load_dictionary(&args.dictionary);
+    eprintln!("Loaded the dictionary in {load_ms:.1}ms");
+    let dictionary_info = api::DictionaryInfo {
+        path: args.dictionary.clone(),
+        words: dictionary::count_words(dictionary),
+        load_ms,
+    };
+    let mut solver = match api::Solver::new(dictionary, dictionary_info, args.threads) {
+        Ok(solver) => solver,
+        Err(e) => quit!("{e}"),
+    };
+    solver.max_swaps = args.max_swaps;
+    if let Some(time_limit) = args.time_limit {
+        solver.time_limit = (!time_limit.is_zero()).then_some(time_limit);
+    }
+    solver
+}
+
+/// Returns deadline of solve that starts now according to `--time-limit` (none by default outside of server modes).
+fn deadline(args: &args::Args) -> Option<std::time::Instant> {
+    args.time_limit
+        .filter(|x| !x.is_zero())
+        .map(|x| std::time::Instant::now() + x)
+}
+
+/// Word filters that don't depend on the board (patterns and word list), so they are only built once in batch mode.
+type WordFilters = (Vec<Regex>, Option<HashSet<String>>);
+
+/// Builds word filters from arguments, quits if they are invalid.
+fn word_filters(args: &args::Args) -> WordFilters {
+    let mut patterns = vec![];
+    if let Some(pattern) = &args.pattern {
+        match spellcast::pattern_to_regex(pattern) {
+            Ok(regex) => patterns.push(regex),
+            Err(e) => quit!("Invalid pattern: {e}"),
+        }
+    }
+    if let Some(regex) = &args.regex {
+        match spellcast::compile_regex(regex) {
+            Ok(regex) => patterns.push(regex),
+            Err(e) => quit!("Invalid regex: {e}"),
+        }
+    }
+    let word_list = args
+        .word_list
+        .as_ref()
+        .map(|path| match std::fs::read_to_string(path) {
+            Ok(content) => content
+                .lines()
+                .map(|x| x.trim().to_ascii_lowercase())
+                .filter(|x| !x.is_empty())
+                .collect(),
+            Err(e) => quit!("Failed to read word list: {e}"),
+        });
+    (patterns, word_list)
+}
+
+/// Parses board and applies arguments to it, returns board, swap count and options.
+/// Errors are returned instead of quitting, so batch mode can skip invalid boards.
+fn prepare(
+    args: &args::Args,
+    board_input: &args::BoardInput,
+    (patterns, word_list): &WordFilters,
+) -> Result<(spellcast::Board, u8, spellcast::SolveOptions), String> {
+    let (mut board, board_swaps) = board_input
+        .parse(args.strict)
+        .map_err(|e| format!("Invalid board:\n{e}"))?;
+    board
+        .set_adjacency(&args.adjacency)
+        .map_err(|e| format!("Invalid adjacency: {e}"))?;
+    board.set_word_multiplier_rule(args.multiplier_rule);
+    let swaps = args.swaps.or(board_swaps).unwrap_or(0);
+    let tiles = |value: &Option<String>| match value {
+        Some(value) => board
+            .parse_tiles(value)
+            .map_err(|e| format!("Invalid tile list: {e}")),
+        None => Ok(vec![]),
+    };
+    let options = spellcast::SolveOptions {
+        include: tiles(&args.include)?,
+        exclude: tiles(&args.exclude)?,
+        start: tiles(&args.start)?,
+        end: tiles(&args.end)?,
+        min_length: args.min_length.unwrap_or(0),
+        max_length: args.max_length,
+        patterns: patterns.clone(),
+        word_list: word_list.clone(),
+    };
+    Ok((board, swaps, options))
+}
+
+/// Solves single board specified in arguments.
+fn solve(args: &args::Args) {
+    let Some(board_input) = &args.board else {
+        quit!("Board is required, use -b to specify it (see --help)");
+    };
+    let filters = word_filters(args);
+    let (board, swaps, options) = match prepare(args, board_input, &filters) {
+        Ok(prepared) => prepared,
+        Err(e) => quit!("{e}"),
+    };
+    if args.show_board && args.format.is_for_humans() {
+        println!("Board: {board}\n{}", board.pretty_grid());
+    }
+    let (dictionary, elapsed_dict) = load_dictionary(&args.dictionary);
     if args.format.is_for_humans() {
         println!("Loaded the dictionary in {elapsed_dict:.1}ms",)
     }
     let clock = std::time::Instant::now();
-    let (words, board) = spellcast::solver_wrapper(
-        args.board,
-        args.swaps,
+    let deadline = deadline(args);
+    let words = spellcast::solver_wrapper(
+        &board,
+        swaps,
         args.threads,
         dictionary,
         args.move_count,
+        &options,
+        spellcast::SolveControl {
+            deadline,
+            ..Default::default()
+        },
     );
     let elapsed_solver = clock.elapsed().as_secs_f64() * 1000.;
+    if deadline.is_some_and(|x| std::time::Instant::now() >= x) {
+        eprintln!("{YELLOW}Time limit reached, better words may exist{RESET}");
+    }
     if args.format.is_for_humans() {
         println!("Solved the board in {elapsed_solver:.1}ms",);
     }
@@ -35,11 +202,152 @@ fn main() {
         output::OutputFormat::Board => {
             output::board_output(&board, words);
         }
-        output::OutputFormat::JSON => {
-            output::json_output(&board, words, elapsed_dict, elapsed_solver);
+        output::OutputFormat::Json => {
+            output::json_output(
+                &board,
+                words,
+                &output::Timings {
+                    dict: elapsed_dict,
+                    solver: elapsed_solver,
+                },
+                &options,
+            );
         }
         output::OutputFormat::Simple => {
             output::simple_output(&board, words);
         }
     }
 }
+
+/// Solves every board from batch input (one board per line, same as `-b`) with one loaded dictionary.
+/// Results are printed as NDJSON, one line per board, invalid boards get `{"line":N,"error":"..."}` instead.
+fn batch(args: &args::Args, batch_args: &args::BatchArgs) {
+    let filters = word_filters(args);
+    let reader: Box<dyn BufRead> = if batch_args.input == "-" {
+        Box::new(std::io::stdin().lock())
+    } else {
+        match std::fs::File::open(&batch_args.input) {
+            Ok(file) => Box::new(std::io::BufReader::new(file)),
+            Err(e) => quit!("Failed to open batch input: {e}"),
+        }
+    };
+    let (dictionary, elapsed_dict) = load_dictionary(&args.dictionary);
+    let clock = std::time::Instant::now();
+    let (mut solved, mut failed) = (0, 0);
+    let mut stdout = std::io::stdout().lock();
+    for (index, line) in reader.lines().enumerate() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => quit!("Failed to read batch input: {e}"),
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let json = match prepare(args, &args::BoardInput(line), &filters) {
+            Ok((board, swaps, options)) => {
+                let board_clock = std::time::Instant::now();
+                let deadline = deadline(args);
+                let words = spellcast::solver_wrapper(
+                    &board,
+                    swaps,
+                    args.threads,
+                    dictionary,
+                    args.move_count,
+                    &options,
+                    spellcast::SolveControl {
+                        deadline,
+                        ..Default::default()
+                    },
+                );
+                let timings = output::Timings {
+                    dict: elapsed_dict,
+                    solver: board_clock.elapsed().as_secs_f64() * 1000.,
+                };
+                solved += 1;
+                serde_json::to_string(&output::BatchOutput {
+                    line: index + 1,
+                    board: board.to_string(),
+                    timed_out: deadline.is_some_and(|x| std::time::Instant::now() >= x),
+                    output: output::JsonOutput::new(&board, &words, &timings, &options),
+                })
+                .unwrap_or_else(|e| quit!("Failed to serialise JSON output: {e}"))
+            }
+            Err(error) => {
+                failed += 1;
+                serde_json::to_string(&output::BatchError {
+                    line: index + 1,
+                    error: &error,
+                })
+                .unwrap_or_else(|e| quit!("Failed to serialise JSON output: {e}"))
+            }
+        };
+        if let Err(e) = writeln!(stdout, "{json}").and_then(|_| stdout.flush()) {
+            quit!("Failed to write batch output: {e}");
+        }
+    }
+    eprintln!(
+        "Solved {solved} boards ({failed} invalid) in {:.1}ms",
+        clock.elapsed().as_secs_f64() * 1000.
+    );
+}
+
+/// Runs benchmark on seeded random boards and prints results.
+/// With `--json -`, tables are printed to stderr, so stdout only contains JSON.
+fn bench(args: &args::Args, bench_args: &args::BenchArgs) {
+    let json_to_stdout = bench_args.json.as_deref() == Some("-");
+    let mut out: Box<dyn Write> = if json_to_stdout {
+        Box::new(std::io::stderr())
+    } else {
+        Box::new(std::io::stdout())
+    };
+    let (dictionary, elapsed_dict) = load_dictionary(&args.dictionary);
+    let report = match bench::run(bench_args, dictionary, elapsed_dict, &mut out) {
+        Ok(report) => report,
+        Err(e) => quit!("{e}"),
+    };
+    if let Err(e) = writeln!(out, "\n{}", bench::markdown_table(&report)) {
+        quit!("Failed to write results: {e}");
+    }
+    if let Some(path) = &bench_args.json {
+        let json = match serde_json::to_string_pretty(&report) {
+            Ok(json) => json,
+            Err(e) => quit!("Failed to serialise benchmark results: {e}"),
+        };
+        if json_to_stdout {
+            println!("{json}");
+        } else if let Err(e) = std::fs::write(path, json + "\n") {
+            quit!("Failed to write benchmark results: {e}");
+        }
+    }
+}
+
+/// Checks move from arguments on the board specified with `-b` and prints it with recomputed score.
+/// Invalid moves are reported like any other error (message on stderr, exit code 1).
+fn verify(args: &args::Args, verify_args: &args::VerifyArgs) {
+    let Some(board_input) = &args.board else {
+        quit!("Board is required, use -b to specify it (see --help)");
+    };
+    let (board, swaps, _) = match prepare(args, board_input, &word_filters(args)) {
+        Ok(prepared) => prepared,
+        Err(e) => quit!("{e}"),
+    };
+    if args.show_board && args.format.is_for_humans() {
+        println!("Board: {board}\n{}", board.pretty_grid());
+    }
+    let steps = match args::read_value(&verify_args.steps, "steps")
+        .and_then(|x| spellcast::Step::list_from_json(&x))
+    {
+        Ok(steps) => steps,
+        Err(e) => quit!("{e}"),
+    };
+    let (dictionary, _) = load_dictionary(&args.dictionary);
+    let word = match spellcast::verify(&board, steps, swaps, dictionary) {
+        Ok(word) => word,
+        Err(e) => quit!("Invalid move: {e}"),
+    };
+    match args.format {
+        output::OutputFormat::Board => output::board_output(&board, vec![word]),
+        output::OutputFormat::Json => output::json_word_output(&board, &word),
+        output::OutputFormat::Simple => output::simple_output(&board, vec![word]),
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
-    spellcast::{Board, Step, Word},
+    quit,
+    spellcast::{Board, ScoreBreakdown, SolveOptions, Step, Word},
     utils::*,
 };
 
@@ -9,136 +10,253 @@ pub enum OutputFormat {
     /// Board output format that prints order of steps on board.
     Board,
     /// JSON output format that is intended for automation purposes.
-    JSON,
+    Json,
     /// Simple output format that prints each word compactly on a single line.
     Simple,
 }
 
 impl OutputFormat {
     /// Returns whether format is intended for humans.
-    /// As of now, it returns `true` for everything other than `JSON`.
+    /// As of now, it returns `true` for everything other than `Json`.
     pub fn is_for_humans(&self) -> bool {
-        !matches!(self, Self::JSON)
+        !matches!(self, Self::Json)
     }
 }
 
+/// Returns human-readable notes for swaps and wildcards used in the word.
+/// Swaps look like `A1 -> x`, wildcards look like `A1 = x`.
+fn step_notes(board: &Board, word: &Word) -> Vec<String> {
+    word.steps
+        .iter()
+        .filter_map(|step| match step {
+            Step::Normal { .. } => None,
+            Step::Swap { index, new_letter } => {
+                Some(format!("{} -> {new_letter}", board.tile_name(*index)))
+            }
+            Step::Wildcard { index, letter } => {
+                Some(format!("{} = {letter}", board.tile_name(*index)))
+            }
+        })
+        .collect()
+}
+
+/// Returns human-readable explanation of word's score, e.g. `(1+5x2+3)x2 + 10 = 38`.
+/// Letter points are listed in word order, `xN` marks multipliers.
+fn score_explanation(board: &Board, word: &Word) -> String {
+    let breakdown = word.breakdown(board);
+    let mut buf = breakdown
+        .letters
+        .iter()
+        .map(|letter| match letter.multiplier {
+            1 => letter.points.to_string(),
+            multiplier => format!("{}x{multiplier}", letter.points),
+        })
+        .collect::<Vec<_>>()
+        .join("+");
+    if breakdown.word_multiplier != 1 {
+        buf = format!("({buf})x{}", breakdown.word_multiplier);
+    }
+    if breakdown.long_word_bonus != 0 {
+        buf += &format!(" + {}", breakdown.long_word_bonus);
+    }
+    format!("{buf} = {}", breakdown.score)
+}
+
 /// Board output format that prints order of steps on board.
 pub fn board_output(board: &Board, words: Vec<Word>) {
+    let label_width = board.height().to_string().len();
+    let mut header = format!("{:<label_width$}  ", "#");
+    for column in 0..board.width() {
+        header += &format!(" {}   ", (b'A' + column as u8) as char);
+    }
+    let header = header.trim_end();
+    let separator = format!(
+        "{:label_width$} {GREY}+{}{RESET}",
+        "",
+        "----+".repeat(board.width())
+    );
     for (i, word) in words.into_iter().enumerate().rev() {
-        let mut order = [None; 25];
-        let mut swaps = vec![];
-        for (i, step) in (&word.steps).into_iter().enumerate() {
-            order[step.index() as usize] = Some((i as i8, step));
-            if let Step::Swap { index, new_letter } = step {
-                swaps.push(format!(
-                    "{}{} -> {new_letter}",
-                    (b'A' + (index % 5) as u8) as char,
-                    index / 5 + 1
-                ));
-            }
+        let mut order = vec![None; board.width() * board.height()];
+        for (i, step) in word.steps.iter().enumerate() {
+            order[step.index() as usize] = Some((i, step));
         }
+        let mut side = vec![
+            word.word(board, true),
+            format!("+{} pts, +{} gems", word.score, word.gems_collected),
+            score_explanation(board, &word),
+        ];
+        if word.swaps_used != 0 {
+            side.push(format!(
+                "-{} gems for {} swap{}",
+                word.swaps_used as u16 * 3,
+                word.swaps_used,
+                if word.swaps_used == 1 { "" } else { "s" }
+            ));
+        }
+        side.extend(step_notes(board, &word));
         println!("===============|{i}|===============");
-        let mut buf =
-            format!("#   A    B    C    D    E\n  {GREY}+----+----+----+----+----+{RESET}\n");
-        for row in 0..5 {
-            buf += &format!("{} {GREY}|{RESET}", row + 1);
-            for column in 0..5 {
-                let index = row * 5 + column;
+        let mut buf = format!("{header}\n{separator}\n");
+        for row in 0..board.height() {
+            buf += &format!("{:<label_width$} {GREY}|{RESET}", row + 1);
+            for column in 0..board.width() {
+                let index = row * board.width() + column;
                 if let Some((i, step)) = order[index] {
-                    if let Step::Swap { new_letter, .. } = step {
-                        buf += &format!("{RED}{new_letter} {GREEN}{i:>2}{GREY}|{RESET}");
-                    } else {
-                        buf += &format!("{} {GREEN}{i:>2}{GREY}|{RESET}", step.letter(board));
+                    match step {
+                        Step::Swap { new_letter, .. } => {
+                            buf += &format!("{RED}{new_letter} {GREEN}{i:>2}{GREY}|{RESET}")
+                        }
+                        Step::Wildcard { letter, .. } => {
+                            buf += &format!("{YELLOW}{letter} {GREEN}{i:>2}{GREY}|{RESET}")
+                        }
+                        Step::Normal { .. } => {
+                            buf += &format!("{} {GREEN}{i:>2}{GREY}|{RESET}", step.letter(board))
+                        }
                     }
                 } else {
                     buf += &format!("    {GREY}|{RESET}");
                 }
             }
-            match row {
-                0 => {
-                    buf += &format!(
-                        " {}\n  {GREY}+----+----+----+----+----+{RESET}\n",
-                        word.word(&board, true)
-                    )
-                }
-                1 => {
-                    buf += &format!(
-                        " +{} pts, +{} gems\n  {GREY}+----+----+----+----+----+{RESET}\n",
-                        word.score, word.gems_collected
-                    )
-                }
-                2..=4 => {
-                    if let Some(swap) = swaps.get(row - 2) {
-                        buf += &format!(" {swap}\n  {GREY}+----+----+----+----+----+{RESET}\n");
-                    } else {
-                        buf += &format!("\n  {GREY}+----+----+----+----+----+{RESET}\n");
-                    }
-                }
-                _ => buf += &format!("\n  {GREY}+----+----+----+----+----+{RESET}\n"),
+            if let Some(line) = side.get(row) {
+                buf += &format!(" {line}");
             }
+            buf += &format!("\n{separator}\n");
+        }
+        // Notes that didn't fit next to the board.
+        for line in side.iter().skip(board.height()) {
+            buf += &format!(
+                "{:indent$}{line}\n",
+                "",
+                indent = label_width + board.width() * 5 + 3
+            );
         }
         println!("{}", buf);
     }
 }
 
+/// Version of JSON output format, see `schema/output.v1.json`.
+/// It is bumped whenever existing fields change or get removed (new fields can be added without bumping it).
+pub const JSON_VERSION: u32 = 1;
+
+/// Time (in milliseconds) spent in different parts of the program.
+#[derive(serde::Serialize)]
+pub struct Timings {
+    /// Time spent loading the dictionary.
+    pub dict: f64,
+    /// Time spent solving the board.
+    pub solver: f64,
+}
+
+/// Board dimensions for JSON output.
+#[derive(serde::Serialize)]
+struct Size {
+    width: usize,
+    height: usize,
+}
+
+/// Word for JSON output, [Word] with fields that need board to be computed.
+#[derive(serde::Serialize)]
+struct JsonWord<'a> {
+    breakdown: ScoreBreakdown,
+    #[serde(flatten)]
+    inner: &'a Word,
+    word: String,
+}
+
+impl JsonWord<'_> {
+    /// Creates JSON word for word on board.
+    fn new<'a>(board: &Board, word: &'a Word) -> JsonWord<'a> {
+        JsonWord {
+            breakdown: word.breakdown(board),
+            inner: word,
+            word: word.word(board, false),
+        }
+    }
+}
+
+/// Whole JSON output, also used as a response by server modes.
+#[derive(serde::Serialize)]
+pub struct JsonOutput<'a> {
+    version: u32,
+    elapsed_ms: &'a Timings,
+    size: Size,
+    options: &'a SolveOptions,
+    words: Vec<JsonWord<'a>>,
+}
+
+impl JsonOutput<'_> {
+    /// Creates JSON output for words found on board.
+    pub fn new<'a>(
+        board: &Board,
+        words: &'a [Word],
+        timings: &'a Timings,
+        options: &'a SolveOptions,
+    ) -> JsonOutput<'a> {
+        JsonOutput {
+            version: JSON_VERSION,
+            elapsed_ms: timings,
+            size: Size {
+                width: board.width(),
+                height: board.height(),
+            },
+            options,
+            words: words
+                .iter()
+                .map(|word| JsonWord::new(board, word))
+                .collect(),
+        }
+    }
+}
+
+/// Single line of batch mode output, [JsonOutput] with the board it belongs to.
+#[derive(serde::Serialize)]
+pub struct BatchOutput<'a> {
+    /// Line number in batch input (starting at 1).
+    pub line: usize,
+    /// Board in canonical form.
+    pub board: String,
+    /// Whether solving was stopped by `--time-limit`, so better words may exist.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+    #[serde(flatten)]
+    pub output: JsonOutput<'a>,
+}
+
+/// Line of batch mode output for board that couldn't be solved.
+#[derive(serde::Serialize)]
+pub struct BatchError<'a> {
+    pub line: usize,
+    pub error: &'a str,
+}
+
 /// JSON output format that is intended for automation purposes.
-pub fn json_output(board: &Board, words: Vec<Word>, elapsed_dict: f64, elapsed_solver: f64) {
-    // Totally real JSON serialisation!!1!
-    // At least it has no dependencies...
-    println!(
-        r#"{{"elapsed_ms":{{"dict":{elapsed_dict:.1},"solver":{elapsed_solver:.1}}},"words":[{}]}}"#,
-        words
-            .into_iter()
-            .map(|word| format!(
-                r#"{{"gems_collected":{},"steps":[{}],"score":{},"swaps_used":{},"word":{:?}}}"#,
-                word.gems_collected,
-                (&word.steps)
-                    .into_iter()
-                    .map(|step| match step {
-                        Step::Normal { index } => format!(r#"{{"swap":false,"index":{index}}}"#),
-                        Step::Swap { index, new_letter } => format!(
-                            r#"{{"swap":true,"index":{index},"new_letter":"{new_letter}"}}"#
-                        ),
-                    })
-                    .collect::<Vec<_>>()
-                    .join(","),
-                word.score,
-                word.swaps_used,
-                word.word(&board, false)
-            ))
-            .collect::<Vec<_>>()
-            .join(",")
-    );
+pub fn json_output(board: &Board, words: Vec<Word>, timings: &Timings, options: &SolveOptions) {
+    match serde_json::to_string(&JsonOutput::new(board, &words, timings, options)) {
+        Ok(json) => println!("{json}"),
+        Err(e) => quit!("Failed to serialise JSON output: {e}"),
+    }
+}
+
+/// JSON output of a single word, same as items of `words` array in [json_output].
+pub fn json_word_output(board: &Board, word: &Word) {
+    match serde_json::to_string(&JsonWord::new(board, word)) {
+        Ok(json) => println!("{json}"),
+        Err(e) => quit!("Failed to serialise JSON output: {e}"),
+    }
 }
 
 /// Simple output format that prints each word compactly on a single line.
 pub fn simple_output(board: &Board, words: Vec<Word>) {
     for (i, word) in words.into_iter().enumerate().rev() {
+        let notes = step_notes(board, &word);
         println!(
             "{i}. {} (+{}pts, +{} gems){}",
-            word.word(&board, true),
+            word.word(board, true),
             word.score,
             word.gems_collected,
-            if word.swaps_used == 0 {
+            if notes.is_empty() {
                 String::new()
             } else {
-                format!(
-                    " / {}",
-                    word.steps
-                        .into_iter()
-                        .filter_map(|step| {
-                            match step {
-                                Step::Normal { .. } => None,
-                                Step::Swap { index, new_letter } => Some(format!(
-                                    "{}{} -> {new_letter}",
-                                    (b'A' + (index % 5) as u8) as char,
-                                    index / 5 + 1
-                                )),
-                            }
-                        })
-                        .collect::<Vec<_>>()
-                        .join(", ")
-                )
+                format!(" / {}", notes.join(", "))
             }
         );
     }
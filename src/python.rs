@@ -0,0 +1,237 @@
+//! Python extension module, built with `--features python` (see pyproject.toml).
+//! It exposes [PyBoard], [PySolver] and [PyWord] as `spellcast_solver.Board`, `spellcast_solver.Solver` and `spellcast_solver.Word`.
+//! Solve options are keyword arguments named like fields of [SolveRequest] (which mirror CLI arguments).
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+use serde_json::Value;
+
+use crate::{
+    api::{AdjacencyValue, SolveRequest},
+    dictionary::{count_words, load_dictionary_file, Node},
+    spellcast::{solver_wrapper, Board, SolveControl, Step},
+};
+
+/// Parsed board, same as `-b` (board string or JSON board).
+#[pyclass(name = "Board", module = "spellcast_solver", frozen)]
+pub struct PyBoard {
+    board: Board,
+    /// Swap count specified by JSON board.
+    swaps: Option<u8>,
+}
+
+#[pymethods]
+impl PyBoard {
+    #[new]
+    #[pyo3(signature = (board, strict = false))]
+    fn new(board: String, strict: bool) -> PyResult<Self> {
+        let (board, swaps) = Board::parse_input(&board, strict).map_err(PyValueError::new_err)?;
+        Ok(PyBoard { board, swaps })
+    }
+
+    #[getter]
+    fn width(&self) -> usize {
+        self.board.width()
+    }
+
+    #[getter]
+    fn height(&self) -> usize {
+        self.board.height()
+    }
+
+    /// Swap count specified by JSON board (None for board strings).
+    #[getter]
+    fn swaps(&self) -> Option<u8> {
+        self.swaps
+    }
+
+    /// Returns board as grid, same as `--show-board`.
+    fn pretty(&self) -> String {
+        self.board.pretty_grid()
+    }
+
+    fn __str__(&self) -> String {
+        self.board.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Board('{}')", self.board)
+    }
+}
+
+/// Board argument of [PySolver::solve], so board strings can be passed directly.
+#[derive(FromPyObject)]
+enum BoardArg<'py> {
+    Board(PyRef<'py, PyBoard>),
+    String(String),
+}
+
+/// Adjacency argument of [PySolver::solve], same as [AdjacencyValue].
+#[derive(FromPyObject)]
+enum AdjacencyArg {
+    Name(String),
+    List(Vec<Vec<u16>>),
+}
+
+/// Single word found by [PySolver::solve].
+#[pyclass(name = "Word", module = "spellcast_solver", frozen, get_all)]
+pub struct PyWord {
+    word: String,
+    score: u16,
+    gems_collected: u8,
+    gems_spent: u16,
+    swaps_used: u8,
+    /// Tile indices of the path (row by row from top left).
+    tiles: Vec<u16>,
+    /// Swaps as (tile name, new letter) pairs, e.g. `("A1", "e")`.
+    swaps: Vec<(String, char)>,
+    /// Letters wildcard tiles are used as, in the same format as `swaps`.
+    wildcards: Vec<(String, char)>,
+}
+
+#[pymethods]
+impl PyWord {
+    fn __repr__(&self) -> String {
+        format!(
+            "Word('{}', score={}, gems_collected={}, swaps_used={})",
+            self.word, self.score, self.gems_collected, self.swaps_used
+        )
+    }
+}
+
+/// Solver with loaded dictionary, it can be reused for any number of boards.
+#[pyclass(name = "Solver", module = "spellcast_solver", frozen)]
+pub struct PySolver {
+    dictionary: Vec<(char, Node)>,
+    threads: u8,
+}
+
+#[pymethods]
+impl PySolver {
+    #[new]
+    #[pyo3(signature = (dictionary = String::from("dictionary.txt"), threads = 1))]
+    fn new(py: Python<'_>, dictionary: String, threads: u8) -> PyResult<Self> {
+        let dictionary = py
+            .allow_threads(|| load_dictionary_file(&dictionary))
+            .map_err(|e| PyValueError::new_err(format!("Failed to load dictionary: {e}")))?;
+        Ok(PySolver {
+            dictionary,
+            threads,
+        })
+    }
+
+    /// Number of words in the dictionary.
+    #[getter]
+    fn words(&self) -> usize {
+        count_words(&self.dictionary)
+    }
+
+    /// Solves the board and returns up to `count` best words.
+    /// GIL is released while solving, so boards can be solved from multiple Python threads at once.
+    #[pyo3(signature = (
+        board, *, swaps = None, count = SolveRequest::default_count(), strict = false, adjacency = None,
+        multiplier_rule = None, include = None, exclude = None, start = None, end = None,
+        min_length = None, max_length = None, pattern = None, regex = None, word_list = None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn solve(
+        &self,
+        py: Python<'_>,
+        board: BoardArg,
+        swaps: Option<u8>,
+        count: u8,
+        strict: bool,
+        adjacency: Option<AdjacencyArg>,
+        multiplier_rule: Option<String>,
+        include: Option<String>,
+        exclude: Option<String>,
+        start: Option<String>,
+        end: Option<String>,
+        min_length: Option<u8>,
+        max_length: Option<u8>,
+        pattern: Option<String>,
+        regex: Option<String>,
+        word_list: Option<Vec<String>>,
+    ) -> PyResult<Vec<PyWord>> {
+        let (board, board_swaps) = match board {
+            BoardArg::Board(board) => (board.board.clone(), board.swaps),
+            BoardArg::String(board) => {
+                Board::parse_input(&board, strict).map_err(PyValueError::new_err)?
+            }
+        };
+        let request = SolveRequest {
+            id: Value::Null,
+            board: Value::Null,
+            swaps,
+            count,
+            strict,
+            adjacency: adjacency.map(|x| match x {
+                AdjacencyArg::Name(name) => AdjacencyValue::Name(name),
+                AdjacencyArg::List(list) => AdjacencyValue::List(list),
+            }),
+            multiplier_rule,
+            include,
+            exclude,
+            start,
+            end,
+            min_length,
+            max_length,
+            pattern,
+            regex,
+            word_list,
+        };
+        let (board, swaps, options) = request
+            .configure(board, board_swaps)
+            .map_err(PyValueError::new_err)?;
+        let words = py.allow_threads(|| {
+            solver_wrapper(
+                &board,
+                swaps,
+                self.threads,
+                &self.dictionary,
+                count,
+                &options,
+                SolveControl::default(),
+            )
+        });
+        Ok(words
+            .iter()
+            .map(|word| PyWord {
+                word: word.word(&board, false),
+                score: word.score,
+                gems_collected: word.gems_collected,
+                gems_spent: word.breakdown(&board).gems_spent,
+                swaps_used: word.swaps_used,
+                tiles: word.steps.iter().map(|x| x.index()).collect(),
+                swaps: word
+                    .steps
+                    .iter()
+                    .filter_map(|x| match x {
+                        Step::Swap { index, new_letter } => {
+                            Some((board.tile_name(*index), *new_letter))
+                        }
+                        _ => None,
+                    })
+                    .collect(),
+                wildcards: word
+                    .steps
+                    .iter()
+                    .filter_map(|x| match x {
+                        Step::Wildcard { index, letter } => {
+                            Some((board.tile_name(*index), *letter))
+                        }
+                        _ => None,
+                    })
+                    .collect(),
+            })
+            .collect())
+    }
+}
+
+/// Module initialiser, public so tests can import the module without building a wheel (see tests/python.rs).
+#[pymodule]
+pub fn spellcast_solver(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyBoard>()?;
+    module.add_class::<PySolver>()?;
+    module.add_class::<PyWord>()?;
+    Ok(())
+}
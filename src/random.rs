@@ -0,0 +1,129 @@
+//! Seeded random boards, used by `bench` subcommand, micro-benchmarks and randomised tests.
+
+use std::{fmt, str::FromStr};
+
+use crate::spellcast::Board;
+
+/// SplitMix64 random number generator.
+/// It is tiny and its output never changes, so the same seed always generates the same boards (unlike `rand` generators, which can change between versions).
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns random number in range [0, 1).
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Relative frequencies (in %) of letters in English texts.
+const ENGLISH_FREQUENCIES: [f64; 26] = [
+    8.2, 1.5, 2.8, 4.3, 12.7, 2.2, 2.0, 6.1, 7.0, 0.15, 0.77, 4.0, 2.4, 6.7, 7.5, 1.9, 0.095, 6.0,
+    6.3, 9.1, 2.8, 0.98, 2.4, 0.15, 2.0, 0.074,
+];
+
+/// Distribution letters of random boards are drawn from.
+/// It is either `uniform`, `english` or custom weights, e.g. `a=8,b=2,e=12` (letters that aren't listed never appear).
+#[derive(Debug, Clone)]
+pub struct LetterDistribution {
+    /// Original string, used to show the distribution in results.
+    name: String,
+    weights: Vec<f64>,
+}
+
+impl Default for LetterDistribution {
+    fn default() -> Self {
+        LetterDistribution {
+            name: String::from("uniform"),
+            weights: vec![1.; 26],
+        }
+    }
+}
+
+impl FromStr for LetterDistribution {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let weights = match s {
+            "uniform" => vec![1.; 26],
+            "english" => ENGLISH_FREQUENCIES.to_vec(),
+            _ => {
+                let mut weights = vec![0.; 26];
+                for item in s.split(',') {
+                    let Some((letter, weight)) = item.split_once('=') else {
+                        return Err(format!(
+                            "Expected uniform/english or a=8,b=2,... but got {item}"
+                        ));
+                    };
+                    let letter = match letter.trim().as_bytes() {
+                        [letter @ b'a'..=b'z'] => letter - b'a',
+                        _ => return Err(format!("Invalid letter {letter}")),
+                    };
+                    weights[letter as usize] = match weight.trim().parse::<f64>() {
+                        Ok(weight) if weight >= 0. && weight.is_finite() => weight,
+                        _ => return Err(format!("Invalid weight {weight}")),
+                    };
+                }
+                if weights.iter().sum::<f64>() <= 0. {
+                    return Err(String::from(
+                        "At least one letter must have positive weight",
+                    ));
+                }
+                weights
+            }
+        };
+        Ok(LetterDistribution {
+            name: s.to_owned(),
+            weights,
+        })
+    }
+}
+
+impl fmt::Display for LetterDistribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl LetterDistribution {
+    /// Draws single random letter.
+    fn sample(&self, rng: &mut Rng) -> char {
+        let mut target = rng.next_f64() * self.weights.iter().sum::<f64>();
+        for (index, weight) in self.weights.iter().enumerate() {
+            if target < *weight {
+                return (b'a' + index as u8) as char;
+            }
+            target -= weight;
+        }
+        // Floating point rounding can leave tiny remainder, so fall back to the last possible letter.
+        let last = self.weights.iter().rposition(|x| *x > 0.).unwrap_or(0);
+        (b'a' + last as u8) as char
+    }
+}
+
+/// Generates `count` random boards of `size`x`size` letters (without any modifiers).
+pub fn generate_boards(
+    seed: u64,
+    count: usize,
+    size: usize,
+    letters: &LetterDistribution,
+) -> Result<Vec<Board>, String> {
+    let mut rng = Rng::new(seed);
+    (0..count)
+        .map(|_| {
+            let board: String = (0..size * size).map(|_| letters.sample(&mut rng)).collect();
+            board.parse()
+        })
+        .collect()
+}
@@ -0,0 +1,359 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+    time::Duration,
+};
+
+use tungstenite::{protocol::WebSocketConfig, Message};
+
+use crate::{
+    api::Solver,
+    utils::{MAX_CONNECTIONS, MAX_REQUEST_BYTES},
+};
+
+/// Number of connections (or HTTP requests) being handled, so servers can refuse ones over [MAX_CONNECTIONS].
+#[derive(Default)]
+struct Connections(AtomicUsize);
+
+/// Open connection counted by [Connections], it is closed when dropped.
+struct Connection<'a>(&'a Connections);
+
+impl Connections {
+    /// Returns new connection, or `None` if there are already [MAX_CONNECTIONS] of them.
+    fn open(&self) -> Option<Connection<'_>> {
+        self.0
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |x| {
+                (x < MAX_CONNECTIONS).then_some(x + 1)
+            })
+            .ok()
+            .map(|_| Connection(self))
+    }
+}
+
+impl Drop for Connection<'_> {
+    fn drop(&mut self) {
+        self.0 .0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Error for connections over [MAX_CONNECTIONS].
+const TOO_MANY_CONNECTIONS: &str = "Too many connections";
+
+/// Returns error for requests bigger than [MAX_REQUEST_BYTES].
+fn too_long_error() -> String {
+    json_error(&format!("Request is longer than {MAX_REQUEST_BYTES} bytes"))
+}
+
+/// Reads requests (one JSON object per line) from `reader` and writes responses (one JSON object per line) to `writer` until EOF.
+/// Empty lines are skipped, lines longer than [MAX_REQUEST_BYTES] get an error without being read into memory.
+fn serve_lines(
+    solver: &Solver,
+    mut reader: impl BufRead,
+    mut writer: impl Write,
+) -> std::io::Result<()> {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let length = (&mut reader)
+            .take(MAX_REQUEST_BYTES as u64 + 1)
+            .read_line(&mut line)?;
+        if length == 0 {
+            return Ok(());
+        }
+        let response = if length > MAX_REQUEST_BYTES && !line.ends_with('\n') {
+            reader.skip_until(b'\n')?;
+            too_long_error()
+        } else if line.trim().is_empty() {
+            continue;
+        } else {
+            let (Ok(response) | Err(response)) = solver.handle(&line);
+            response
+        };
+        writeln!(writer, "{response}")?;
+        writer.flush()?;
+    }
+}
+
+/// Serves requests from stdin, writing responses to stdout.
+pub fn serve_stdio(solver: &Solver) -> std::io::Result<()> {
+    serve_lines(solver, std::io::stdin().lock(), std::io::stdout().lock())
+}
+
+/// Serves requests on Unix socket @ `path`, each connection is handled on its own thread.
+/// Stale socket file left by previous server is replaced, but other files aren't touched.
+/// Connections over [MAX_CONNECTIONS] get an error line and are closed.
+#[cfg(unix)]
+pub fn serve_socket(solver: &Solver, path: &str) -> std::io::Result<()> {
+    use std::os::unix::{fs::FileTypeExt, net::UnixListener};
+    if let Ok(metadata) = std::fs::metadata(path) {
+        if metadata.file_type().is_socket() {
+            std::fs::remove_file(path)?;
+        }
+    }
+    let listener = UnixListener::bind(path)?;
+    eprintln!("Listening on {path}");
+    let connections = Connections::default();
+    std::thread::scope(|scope| {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(mut stream) => {
+                    let Some(connection) = connections.open() else {
+                        let _ = writeln!(stream, "{}", json_error(TOO_MANY_CONNECTIONS));
+                        continue;
+                    };
+                    scope.spawn(move || {
+                        let _connection = connection;
+                        let reader = match stream.try_clone() {
+                            Ok(reader) => BufReader::new(reader),
+                            Err(e) => return eprintln!("Failed to clone connection: {e}"),
+                        };
+                        if let Err(e) = serve_lines(solver, reader, stream) {
+                            eprintln!("Connection failed: {e}");
+                        }
+                    });
+                }
+                Err(e) => eprintln!("Failed to accept connection: {e}"),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Unix sockets are only available on Unix.
+#[cfg(not(unix))]
+pub fn serve_socket(_solver: &Solver, _path: &str) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "Unix sockets are not supported on this platform",
+    ))
+}
+
+/// Serves HTTP API on `listener`, each request is handled on its own thread.
+/// `POST /solve` takes solve request and returns the same response as [serve_stdio] (with 400 status for errors).
+/// `GET /health` returns `{"status":"ok"}` and `GET /dictionary` returns [crate::api::DictionaryInfo].
+/// CORS headers are only sent for origins listed in `cors_origins` (`*` allows any origin).
+/// Requests over [MAX_CONNECTIONS] get 503 status and bodies bigger than [MAX_REQUEST_BYTES] get 413 status.
+pub fn serve_http(
+    solver: &Solver,
+    listener: TcpListener,
+    cors_origins: &[String],
+) -> std::io::Result<()> {
+    let server = tiny_http::Server::from_listener(listener, None).map_err(std::io::Error::other)?;
+    eprintln!("Listening on http://{}", server.server_addr());
+    let connections = Connections::default();
+    std::thread::scope(|scope| {
+        for request in server.incoming_requests() {
+            let Some(connection) = connections.open() else {
+                let response = tiny_http::Response::from_string(json_error(TOO_MANY_CONNECTIONS))
+                    .with_status_code(503);
+                if let Err(e) = request.respond(response) {
+                    eprintln!("Failed to respond: {e}");
+                }
+                continue;
+            };
+            scope.spawn(move || {
+                let _connection = connection;
+                if let Err(e) = handle_http(solver, request, cors_origins) {
+                    eprintln!("Failed to respond: {e}");
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+/// Handles single HTTP request for [serve_http].
+fn handle_http(
+    solver: &Solver,
+    mut request: tiny_http::Request,
+    cors_origins: &[String],
+) -> std::io::Result<()> {
+    let header = |name: &str, value: &str| {
+        tiny_http::Header::from_bytes(name.as_bytes(), value.as_bytes())
+            .expect("header should be valid")
+    };
+    let origin = request
+        .headers()
+        .iter()
+        .find(|x| x.field.equiv("Origin"))
+        .map(|x| x.value.to_string());
+    let mut headers = vec![header("Content-Type", "application/json")];
+    if let Some(origin) = origin {
+        if cors_origins.iter().any(|x| x == "*" || *x == origin) {
+            headers.push(header("Access-Control-Allow-Origin", &origin));
+            headers.push(header("Access-Control-Allow-Methods", "GET, POST, OPTIONS"));
+            headers.push(header("Access-Control-Allow-Headers", "Content-Type"));
+            headers.push(header("Vary", "Origin"));
+        }
+    }
+    let path = request
+        .url()
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .to_owned();
+    let (status, body) = match (request.method(), path.as_str()) {
+        (tiny_http::Method::Options, _) => (204, String::new()),
+        (tiny_http::Method::Get, "/health") => (200, String::from(r#"{"status":"ok"}"#)),
+        (tiny_http::Method::Get, "/dictionary") => (
+            200,
+            serde_json::to_string(&solver.dictionary_info).map_err(std::io::Error::other)?,
+        ),
+        (tiny_http::Method::Post, "/solve") => {
+            let mut body = String::new();
+            // Body length isn't known for chunked requests, so it is checked after reading as well.
+            let result = match request.body_length() {
+                Some(length) if length > MAX_REQUEST_BYTES => Ok(length),
+                _ => request
+                    .as_reader()
+                    .take(MAX_REQUEST_BYTES as u64 + 1)
+                    .read_to_string(&mut body),
+            };
+            match result {
+                Ok(length) if length > MAX_REQUEST_BYTES => (413, too_long_error()),
+                Ok(_) => match solver.handle(&body) {
+                    Ok(response) => (200, response),
+                    Err(response) => (400, response),
+                },
+                Err(e) => (400, json_error(&format!("Failed to read body: {e}"))),
+            }
+        }
+        (_, "/solve" | "/health" | "/dictionary") => (405, json_error("Method not allowed")),
+        _ => (404, json_error("Not found")),
+    };
+    let mut response = tiny_http::Response::from_string(body).with_status_code(status);
+    for header in headers {
+        response.add_header(header);
+    }
+    request.respond(response)
+}
+
+/// Serves WebSocket API on `listener`, each connection is handled on its own thread.
+/// Every text message is a solve request, responses are sent as text messages in the same format as [serve_stdio].
+/// While the search is running, best-so-far results are pushed with `"partial":true` whenever they improve.
+/// New request cancels the search of the previous one on the same connection, so only results of the latest request are sent.
+/// Connections over [MAX_CONNECTIONS] are refused with 503 status and messages bigger than [MAX_REQUEST_BYTES] close the connection.
+pub fn serve_websocket(solver: &Solver, listener: TcpListener) -> std::io::Result<()> {
+    eprintln!("Listening on ws://{}", listener.local_addr()?);
+    let connections = Connections::default();
+    std::thread::scope(|scope| {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(mut stream) => {
+                    let Some(connection) = connections.open() else {
+                        // Refuse the handshake, so clients get an error right away.
+                        let body = json_error(TOO_MANY_CONNECTIONS);
+                        let _ = write!(
+                            stream,
+                            "HTTP/1.1 503 Service Unavailable\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                            body.len()
+                        );
+                        continue;
+                    };
+                    scope.spawn(move || {
+                        let _connection = connection;
+                        if let Err(e) = handle_websocket(solver, stream) {
+                            eprintln!("Connection failed: {e}");
+                        }
+                    });
+                }
+                Err(e) => eprintln!("Failed to accept connection: {e}"),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Handles single WebSocket connection for [serve_websocket].
+/// Searches run on their own threads and send responses tagged with request number over a channel.
+/// Reads time out regularly, so responses can be forwarded while waiting for the next request.
+fn handle_websocket(solver: &Solver, stream: TcpStream) -> Result<(), String> {
+    let config = WebSocketConfig {
+        max_message_size: Some(MAX_REQUEST_BYTES),
+        max_frame_size: Some(MAX_REQUEST_BYTES),
+        ..WebSocketConfig::default()
+    };
+    let mut socket = tungstenite::accept_with_config(stream, Some(config))
+        .map_err(|e| format!("Handshake failed: {e}"))?;
+    socket
+        .get_ref()
+        .set_read_timeout(Some(Duration::from_millis(20)))
+        .map_err(|e| e.to_string())?;
+    let (sender, receiver) = mpsc::channel::<(u64, String)>();
+    let mut generation = 0;
+    let mut cancel = Arc::new(AtomicBool::new(false));
+    std::thread::scope(|scope| {
+        let result = loop {
+            let request = match socket.read() {
+                Ok(Message::Text(text)) => Some(text),
+                Ok(Message::Binary(_)) => {
+                    let error = json_error("Expected text message");
+                    if let Err(e) = socket.send(Message::Text(error)) {
+                        break Err(e.to_string());
+                    }
+                    None
+                }
+                // Pings and close frames are handled by tungstenite.
+                Ok(_) => None,
+                Err(tungstenite::Error::Io(e))
+                    if matches!(
+                        e.kind(),
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    None
+                }
+                Err(
+                    tungstenite::Error::ConnectionClosed
+                    | tungstenite::Error::Protocol(
+                        tungstenite::error::ProtocolError::ResetWithoutClosingHandshake,
+                    ),
+                ) => break Ok(()),
+                Err(tungstenite::Error::Capacity(e)) => {
+                    let _ = socket.send(Message::Text(too_long_error()));
+                    break Err(e.to_string());
+                }
+                Err(e) => break Err(e.to_string()),
+            };
+            if let Some(request) = request {
+                cancel.store(true, Ordering::Relaxed);
+                cancel = Arc::new(AtomicBool::new(false));
+                generation += 1;
+                let cancel = cancel.clone();
+                let sender = sender.clone();
+                scope.spawn(move || {
+                    let partial = |response| {
+                        let _ = sender.send((generation, response));
+                    };
+                    let (Ok(response) | Err(response)) =
+                        solver.handle_live(&request, Some(&cancel), Some(&partial));
+                    let _ = sender.send((generation, response));
+                });
+            }
+            // Responses of cancelled requests are dropped.
+            let mut result = Ok(());
+            while let Ok((response_generation, response)) = receiver.try_recv() {
+                if response_generation == generation {
+                    result = socket.send(Message::Text(response));
+                    if result.is_err() {
+                        break;
+                    }
+                }
+            }
+            if let Err(e) = result {
+                break Err(e.to_string());
+            }
+        };
+        cancel.store(true, Ordering::Relaxed);
+        result
+    })
+}
+
+/// Returns `{"error":"..."}` JSON.
+fn json_error(error: &str) -> String {
+    serde_json::json!({ "error": error }).to_string()
+}
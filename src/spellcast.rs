@@ -1,8 +1,19 @@
-use std::str::FromStr;
+use std::{
+    collections::HashSet,
+    fmt,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::Instant,
+};
+
+use regex::{Regex, RegexBuilder};
 
 use crate::{
-    dictionary::Node,
-    utils::{MAX_SOLUTIONS, RED, RESET},
+    dictionary::{contains_word, filter_dictionary_tree, Node},
+    utils::{MAX_FAST_SWAPS, MAX_SOLUTIONS, RED, RESET, YELLOW},
 };
 
 /// Returns points given for a specific letter.
@@ -21,7 +32,7 @@ fn get_letter_points(letter: char) -> u8 {
 }
 
 /// Spellcast tile.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Tile {
     letter: char,
     letter_multiplier: u8,
@@ -31,6 +42,28 @@ struct Tile {
 }
 
 impl Tile {
+    /// Returns tile in board string syntax, modifiers are always in the same order (`$`, `+`/`*`, `!`, `#`).
+    fn to_board_string(&self) -> String {
+        let mut buf = self.letter.to_string();
+        match self.word_multiplier {
+            1 => (),
+            2 => buf.push('$'),
+            multiplier => buf += &format!("${multiplier}"),
+        }
+        match self.letter_multiplier {
+            2 => buf.push('+'),
+            3 => buf.push('*'),
+            _ => (),
+        }
+        if self.gem {
+            buf.push('!');
+        }
+        if self.frozen {
+            buf.push('#');
+        }
+        buf
+    }
+
     /// Returns new tile with specified letter that doesn't have any special properties (no multipliers, no gem, not frozen).
     fn empty(letter: char) -> Self {
         Tile {
@@ -43,10 +76,285 @@ impl Tile {
     }
 }
 
-/// Spellcast board.
+/// Rule that determines which tiles are adjacent to each other.
 #[derive(Debug)]
+pub enum Adjacency {
+    /// Tiles on a grid are adjacent to tiles next to them.
+    /// With `diagonals`, tiles touching by corner are adjacent too (8 neighbors, standard Spellcast rule), otherwise there are only 4 neighbors.
+    /// With `wraparound`, board edges wrap around (i.e. leftmost column is adjacent to rightmost one, same for top and bottom rows).
+    Grid { diagonals: bool, wraparound: bool },
+    /// Custom list of adjacent tile indices for each tile.
+    /// Adjacency doesn't have to be mutual, step can go from tile to any tile in its list.
+    Custom(Vec<Vec<u16>>),
+}
+
+impl Default for Adjacency {
+    fn default() -> Self {
+        Adjacency::Grid {
+            diagonals: true,
+            wraparound: false,
+        }
+    }
+}
+
+impl Adjacency {
+    /// Parses name of grid rule (`8way`, `4way`, `8way-wrap` or `4way-wrap`).
+    /// Custom adjacency lists are parsed with [Adjacency::parse_list] instead.
+    pub fn from_name(s: &str) -> Result<Adjacency, String> {
+        let (diagonals, wraparound) = match s {
+            "8way" => (true, false),
+            "4way" => (false, false),
+            "8way-wrap" => (true, true),
+            "4way-wrap" => (false, true),
+            _ => return Err(String::from("Expected 8way/4way/8way-wrap/4way-wrap")),
+        };
+        Ok(Adjacency::Grid {
+            diagonals,
+            wraparound,
+        })
+    }
+
+    /// Parses custom adjacency list.
+    /// Each line lists 0-based indices of tiles adjacent to a tile (first line is for tile 0 and so on), separated by spaces or commas.
+    /// Empty lines are kept (tile has no neighbors), lines starting with `//` are comments.
+    pub fn parse_list(s: &str) -> Result<Adjacency, String> {
+        let mut list = vec![];
+        for (line_index, line) in s.lines().enumerate() {
+            if line.trim_start().starts_with("//") {
+                continue;
+            }
+            let mut tile_neighbors = vec![];
+            for item in line.split([' ', ',', '\t']).filter(|x| !x.is_empty()) {
+                tile_neighbors.push(item.parse::<u16>().map_err(|e| {
+                    format!("Line {}: invalid tile index {item:?} ({e})", line_index + 1)
+                })?);
+            }
+            list.push(tile_neighbors);
+        }
+        Ok(Adjacency::Custom(list))
+    }
+}
+
+/// Rule that determines how word multipliers of multiple tiles in one word combine.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum WordMultiplierRule {
+    /// Only the highest multiplier is used (standard Spellcast rule, two 2x tiles still give 2x).
+    #[default]
+    Max,
+    /// Multipliers stack multiplicatively (two 2x tiles give 4x).
+    Multiply,
+}
+
+impl FromStr for WordMultiplierRule {
+    type Err = String;
+
+    /// Parses rule name (`max` or `multiply`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "max" => Ok(Self::Max),
+            "multiply" => Ok(Self::Multiply),
+            _ => Err(String::from("Expected max/multiply")),
+        }
+    }
+}
+
+impl WordMultiplierRule {
+    /// Combines word multiplier accumulated so far with multiplier of another tile.
+    fn combine(self, accumulated: u32, multiplier: u8) -> u32 {
+        match self {
+            WordMultiplierRule::Max => accumulated.max(multiplier as u32),
+            WordMultiplierRule::Multiply => accumulated.saturating_mul(multiplier as u32),
+        }
+    }
+}
+
+/// Spellcast board.
+/// Tiles are stored row by row, so tile @ `index` is in column `index % self.width` and row `index / self.width`.
+#[derive(Debug, Clone)]
 pub struct Board {
-    tiles: [Tile; 25],
+    tiles: Vec<Tile>,
+    width: usize,
+    height: usize,
+    /// Indices of tiles adjacent to each tile, precomputed once so [solver] doesn't need to do any math.
+    /// Determined by [Adjacency] rule (see [Board::set_adjacency]).
+    neighbors: Vec<Vec<u16>>,
+    /// See [Board::set_word_multiplier_rule].
+    word_multiplier_rule: WordMultiplierRule,
+}
+
+impl Board {
+    /// Creates new board from tiles (row by row) with default [Adjacency].
+    fn new(tiles: Vec<Tile>, width: usize, height: usize) -> Board {
+        let mut board = Board {
+            tiles,
+            width,
+            height,
+            neighbors: vec![],
+            word_multiplier_rule: WordMultiplierRule::default(),
+        };
+        board.neighbors = board.grid_neighbors(true, false);
+        board
+    }
+
+    /// Calculates neighbors for each tile on a grid.
+    /// Neighbors are always listed in the same order (column by column, top to bottom), so results don't depend on rule used.
+    fn grid_neighbors(&self, diagonals: bool, wraparound: bool) -> Vec<Vec<u16>> {
+        let width = self.width as isize;
+        let height = self.height as isize;
+        let mut neighbors = Vec::with_capacity(self.tiles.len());
+        for index in 0..self.tiles.len() as isize {
+            let x = index % width;
+            let y = index / width;
+            let mut tile_neighbors = vec![];
+            for dx in [-1, 0, 1] {
+                for dy in [-1, 0, 1] {
+                    if (dx == 0 && dy == 0) || (!diagonals && dx != 0 && dy != 0) {
+                        continue;
+                    }
+                    let mut nx = x + dx;
+                    let mut ny = y + dy;
+                    if wraparound {
+                        nx = nx.rem_euclid(width);
+                        ny = ny.rem_euclid(height);
+                    } else if nx < 0 || nx >= width || ny < 0 || ny >= height {
+                        continue;
+                    }
+                    let ni = (ny * width + nx) as u16;
+                    // On tiny boards wrapping around can lead back to the same tile or to the same neighbor twice.
+                    if ni as isize != index && !tile_neighbors.contains(&ni) {
+                        tile_neighbors.push(ni);
+                    }
+                }
+            }
+            neighbors.push(tile_neighbors);
+        }
+        neighbors
+    }
+
+    /// Replaces neighbors of each tile according to `adjacency` rule.
+    /// Fails if custom adjacency list doesn't match the board.
+    pub fn set_adjacency(&mut self, adjacency: &Adjacency) -> Result<(), String> {
+        self.neighbors = match adjacency {
+            Adjacency::Grid {
+                diagonals,
+                wraparound,
+            } => self.grid_neighbors(*diagonals, *wraparound),
+            Adjacency::Custom(list) => {
+                if list.len() != self.tiles.len() {
+                    return Err(format!(
+                        "Expected adjacency list for {} tiles, but got {}",
+                        self.tiles.len(),
+                        list.len()
+                    ));
+                }
+                for (index, tile_neighbors) in list.iter().enumerate() {
+                    if let Some(ni) = tile_neighbors
+                        .iter()
+                        .find(|x| **x as usize >= self.tiles.len() || **x as usize == index)
+                    {
+                        return Err(format!("Tile {index} can't be adjacent to tile {ni}"));
+                    }
+                }
+                list.clone()
+            }
+        };
+        Ok(())
+    }
+
+    /// Sets rule used to combine word multipliers of multiple tiles in one word.
+    pub fn set_word_multiplier_rule(&mut self, rule: WordMultiplierRule) {
+        self.word_multiplier_rule = rule;
+    }
+
+    /// Returns number of columns.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns number of rows.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns chess-like name of tile @ `index` (e.g. `A1` for top-left tile).
+    pub fn tile_name(&self, index: u16) -> String {
+        let index = index as usize;
+        format!(
+            "{}{}",
+            (b'A' + (index % self.width) as u8) as char,
+            index / self.width + 1
+        )
+    }
+
+    /// Returns human-readable grid with column letters and row numbers, intended for logs.
+    /// Each cell is a tile in board string syntax (e.g. `a$*!` is a 2x TL tile with a gem).
+    pub fn pretty_grid(&self) -> String {
+        let cells: Vec<String> = self.tiles.iter().map(|x| x.to_board_string()).collect();
+        let cell_width = cells.iter().map(|x| x.len()).max().unwrap_or(1).max(2);
+        let label_width = self.height.to_string().len();
+        let mut buf = format!("{:label_width$} ", "");
+        for column in 0..self.width {
+            buf += &format!(" {:<cell_width$}", (b'A' + column as u8) as char);
+        }
+        buf = buf.trim_end().to_owned() + "\n";
+        let separator = format!(
+            "{:label_width$} +{}\n",
+            "",
+            format!("{}+", "-".repeat(cell_width)).repeat(self.width)
+        );
+        buf += &separator;
+        for row in 0..self.height {
+            buf += &format!("{:<label_width$} |", row + 1);
+            for cell in &cells[row * self.width..(row + 1) * self.width] {
+                buf += &format!("{cell:<cell_width$}|");
+            }
+            buf += "\n";
+            buf += &separator;
+        }
+        buf
+    }
+
+    /// Parses comma-separated list of chess-like tile names (e.g. `A1,C3`) into tile indices.
+    pub fn parse_tiles(&self, s: &str) -> Result<Vec<u16>, String> {
+        let mut indices = vec![];
+        for name in s.split(',').map(|x| x.trim()).filter(|x| !x.is_empty()) {
+            let mut chars = name.chars();
+            let column = match chars.next() {
+                Some(column) if column.is_ascii_alphabetic() => {
+                    (column.to_ascii_uppercase() as u8 - b'A') as usize
+                }
+                _ => return Err(format!("Invalid tile name {name:?}")),
+            };
+            let row = match chars.as_str().parse::<usize>() {
+                Ok(row) if row > 0 => row - 1,
+                _ => return Err(format!("Invalid tile name {name:?}")),
+            };
+            if column >= self.width || row >= self.height {
+                return Err(format!("Tile {name} is outside of the board"));
+            }
+            indices.push((row * self.width + column) as u16);
+        }
+        Ok(indices)
+    }
+}
+
+impl fmt::Display for Board {
+    /// Formats the board as canonical board string (see [Board::from_str]).
+    /// Square boards are written as a single row, other boards have rows separated by `/`.
+    /// Boards with a single row (other than 1x1) end with `/`, so they aren't read back as square.
+    /// Adjacency and word multiplier rule aren't part of board string, so they aren't included.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, tile) in self.tiles.iter().enumerate() {
+            if index != 0 && index % self.width == 0 && self.width != self.height {
+                write!(f, "/")?;
+            }
+            write!(f, "{}", tile.to_board_string())?;
+        }
+        if self.height == 1 && self.width > 1 {
+            write!(f, "/")?;
+        }
+        Ok(())
+    }
 }
 
 impl FromStr for Board {
@@ -55,39 +363,357 @@ impl FromStr for Board {
     /// Parses the board string into actual board.
     /// Board string syntax is based on one WintrCat made.
     /// Each tile is represented by a letter than can have postfix consisting of the following characters:
-    /// `$` - 2x word multiplier;
+    /// `$` - 2x word multiplier, can be followed by a digit for other multipliers (e.g. `$3` for 3x);
     /// `+`/`*` - DL/TL letter multiplier;
     /// `!` - tile has a gem;
     /// `#` - frozen tile;
+    /// Letter can also be replaced with `?`, which marks a wildcard tile (unknown letter that can be used as any letter for free).
+    /// Rows are separated by newlines or `/`, all rows must have the same number of tiles.
+    /// If there is only one row, board is assumed to be square (e.g. 25 tiles make 5x5 board), unless the row is followed by `/` (e.g. `cat/` is 3x1 board).
     /// However, this parser is much more lenient than original.
-    /// Any characters other than ones described above will be silently ignored.
+    /// Any characters other than ones described above will be silently ignored (rows without any tiles are ignored too).
     /// This means it is compatible with original format that has newlines and numbers at the bottom.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut tiles = vec![];
+        let mut tiles: Vec<Tile> = vec![];
+        let mut rows = vec![0];
+        // Whether rows are separated by `/`, which makes even a single row an actual row.
+        let mut slash = false;
+        let mut previous = ' ';
         for char in s.to_lowercase().chars() {
             if let Some(last) = tiles.last_mut() {
                 match char {
-                    'a'..='z' => tiles.push(Tile::empty(char)),
+                    '1'..='9' if previous == '$' => {
+                        last.word_multiplier = char.to_digit(10).unwrap() as u8
+                    }
+                    'a'..='z' | '?' => {
+                        tiles.push(Tile::empty(char));
+                        *rows.last_mut().unwrap() += 1;
+                    }
                     '$' => last.word_multiplier = 2,
                     '+' => last.letter_multiplier = 2,
                     '*' => last.letter_multiplier = 3,
                     '!' => last.gem = true,
                     '#' => last.frozen = true,
+                    '\n' => rows.push(0),
+                    '/' => {
+                        rows.push(0);
+                        slash = true;
+                    }
                     _ => (),
                 }
-            } else if matches!(char, 'a'..='z') {
+            } else if char.is_ascii_lowercase() || char == '?' {
                 tiles.push(Tile::empty(char));
+                *rows.last_mut().unwrap() += 1;
             }
+            previous = char;
         }
+        rows.retain(|x| *x != 0);
+        let count = tiles.len();
+        let (width, height) = match rows[..] {
+            [] => return Err("Expected at least one tile, but got 0".into()),
+            [_] if !slash => {
+                let side = (count as f64).sqrt().round() as usize;
+                if side * side != count {
+                    return Err(format!(
+                        "Expected square number of tiles (e.g. 25 for 5x5), but got {count}"
+                    ));
+                }
+                (side, side)
+            }
+            [first, ..] => {
+                if rows.iter().any(|x| *x != first) {
+                    return Err(format!(
+                        "Expected all rows to have the same number of tiles, but got {rows:?}"
+                    ));
+                }
+                (first, rows.len())
+            }
+        };
+        Board::with_size(tiles, width, height)
+    }
+}
+
+/// Board in JSON format, see [Board::from_json].
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonBoard {
+    tiles: Vec<serde_json::Value>,
+    width: Option<usize>,
+    height: Option<usize>,
+    gems: Option<u8>,
+    swaps: Option<u8>,
+}
+
+/// Tile in JSON format, see [Board::from_json].
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonTile {
+    letter: String,
+    #[serde(default = "JsonTile::one")]
+    letter_multiplier: u8,
+    #[serde(default = "JsonTile::one")]
+    word_multiplier: u8,
+    #[serde(default)]
+    gem: bool,
+    #[serde(default)]
+    frozen: bool,
+}
+
+impl JsonTile {
+    /// Default multiplier for serde.
+    fn one() -> u8 {
+        1
+    }
+}
+
+impl Board {
+    /// Creates new board after checking that tiles fit into `width`x`height` grid and that board isn't too big.
+    fn with_size(tiles: Vec<Tile>, width: usize, height: usize) -> Result<Board, String> {
         let count = tiles.len();
-        if count != 25 {
-            return Err(format!("Expected 25 tiles, but got {count}"));
+        if width == 0 || height == 0 || width.checked_mul(height) != Some(count) {
+            return Err(format!(
+                "Expected {width}x{height} board to have {} tiles, but got {count}",
+                width as u128 * height as u128
+            ));
         }
-        Ok(Board {
-            tiles: tiles
-                .try_into()
-                .map_err(|_| "Failed to convert Vec<Tile> to [Tile; 25]".to_string())?,
-        })
+        if width > 26 {
+            return Err(format!("Expected at most 26 columns, but got {width}"));
+        }
+        if count > u16::MAX as usize {
+            return Err(format!(
+                "Expected at most {} tiles, but got {count}",
+                u16::MAX
+            ));
+        }
+        Ok(Board::new(tiles, width, height))
+    }
+
+    /// Parses board string like [Board::from_str], but reports every problem lenient parser would silently ignore.
+    /// Problems are unknown characters, modifiers before the first letter, duplicate modifiers (e.g. two letter multipliers on one tile), empty rows and rows of different lengths.
+    /// Each problem is reported on its own line and starts with tile name (or row number) and character offset, e.g. `C4 (offset 17): two letter multipliers`.
+    pub fn parse_strict(s: &str) -> Result<Board, String> {
+        /// Where the problem is, used to prefix the message.
+        enum Location {
+            Tile(usize),
+            Row(usize),
+            Offset,
+        }
+        let mut problems: Vec<(Location, usize, String)> = vec![];
+        // (number of tiles, offset of the row start)
+        let mut rows = vec![(0, 0)];
+        let mut count: usize = 0;
+        let mut previous = ' ';
+        // Letter multiplier, word multiplier, gem, frozen.
+        let mut seen = [false; 4];
+        let trimmed = s.trim_end();
+        for (offset, char) in trimmed.chars().enumerate() {
+            let char = char.to_ascii_lowercase();
+            let modifier = match char {
+                'a'..='z' | '?' => {
+                    count += 1;
+                    rows.last_mut().unwrap().0 += 1;
+                    seen = [false; 4];
+                    None
+                }
+                '1'..='9' if previous == '$' => None,
+                '+' | '*' => Some((0, "two letter multipliers")),
+                '$' => Some((1, "two word multipliers")),
+                '!' => Some((2, "two gems")),
+                '#' => Some((3, "tile is frozen twice")),
+                '\n' | '/' => {
+                    if rows.last().unwrap().0 == 0 {
+                        problems.push((Location::Row(rows.len()), offset, "empty row".into()));
+                    }
+                    rows.push((0, offset + 1));
+                    None
+                }
+                ' ' | '\t' | '\r' => None,
+                _ => {
+                    let message = format!("unknown character {char:?}");
+                    match count.checked_sub(1) {
+                        Some(index) => problems.push((Location::Tile(index), offset, message)),
+                        None => problems.push((Location::Offset, offset, message)),
+                    }
+                    None
+                }
+            };
+            if let Some((kind, message)) = modifier {
+                if count == 0 {
+                    problems.push((
+                        Location::Offset,
+                        offset,
+                        format!("modifier {char:?} before first letter"),
+                    ));
+                } else if seen[kind] {
+                    problems.push((Location::Tile(count - 1), offset, message.into()));
+                }
+                seen[kind] = true;
+            }
+            previous = char;
+        }
+        // Trailing `/` after the only row makes it single-row board, so it doesn't end with an empty row.
+        let single_row = rows.len() == 2 && rows[1].0 == 0 && trimmed.ends_with('/');
+        if rows.len() > 1 && rows.last().unwrap().0 == 0 && !single_row {
+            problems.push((
+                Location::Row(rows.len()),
+                trimmed.chars().count(),
+                "empty row".into(),
+            ));
+        }
+        let first = rows[0].0;
+        if rows.len() > 1 {
+            for (row, (length, offset)) in rows.iter().enumerate() {
+                if *length != first && *length != 0 {
+                    problems.push((
+                        Location::Row(row + 1),
+                        *offset,
+                        format!("expected {first} tiles, but got {length}"),
+                    ));
+                }
+            }
+        }
+        let width = if rows.len() > 1 {
+            first
+        } else {
+            (count as f64).sqrt().round() as usize
+        };
+        let mut messages: Vec<String> = problems
+            .into_iter()
+            .map(|(location, offset, message)| match location {
+                Location::Tile(index) if width > 0 && width <= 26 => format!(
+                    "{}{} (offset {offset}): {message}",
+                    (b'A' + (index % width) as u8) as char,
+                    index / width + 1
+                ),
+                Location::Tile(index) => format!("Tile {index} (offset {offset}): {message}"),
+                Location::Row(row) => format!("Row {row} (offset {offset}): {message}"),
+                Location::Offset => format!("Offset {offset}: {message}"),
+            })
+            .collect();
+        let board = s.parse::<Board>();
+        if messages.is_empty() {
+            return board;
+        }
+        if let Err(e) = board {
+            // Row length problems are already reported in more detail.
+            if !e.starts_with("Expected all rows") {
+                messages.push(e);
+            }
+        }
+        Err(messages.join("\n"))
+    }
+
+    /// Parses board in JSON format, an alternative to board string that is easier to generate from structured data.
+    /// It is an object with `tiles` array (row by row) and optional `width`, `height`, `gems` and `swaps`, or just `tiles` array on its own.
+    /// Each tile is an object with `letter` (single letter or `?` for wildcard) and optional `letter_multiplier` (1-3), `word_multiplier` (1-9), `gem` and `frozen`.
+    /// If neither `width` nor `height` is specified, board is assumed to be square.
+    /// Returns board and number of swaps available (`swaps` or, if only `gems` are specified, `gems / 3`).
+    /// Errors about specific tiles start with tile name and index (e.g. `B1 (tile 1): ...`).
+    pub fn from_json(s: &str) -> Result<(Board, Option<u8>), String> {
+        Board::from_json_value(serde_json::from_str(s).map_err(|e| format!("Invalid JSON: {e}"))?)
+    }
+
+    /// Parses board string or JSON board and returns it together with number of swaps available (only JSON board can specify it).
+    /// With `strict`, board string is parsed with [Board::parse_strict].
+    pub fn parse_input(s: &str, strict: bool) -> Result<(Board, Option<u8>), String> {
+        // Board strings can't start with { or [, so there is no need for separate argument.
+        if s.trim_start().starts_with(['{', '[']) {
+            Board::from_json(s)
+        } else if strict {
+            Ok((Board::parse_strict(s)?, None))
+        } else {
+            Ok((s.parse()?, None))
+        }
+    }
+
+    /// Same as [Board::from_json], but for already parsed JSON (e.g. part of a bigger request).
+    pub fn from_json_value(value: serde_json::Value) -> Result<(Board, Option<u8>), String> {
+        let json = if value.is_array() {
+            JsonBoard {
+                tiles: serde_json::from_value(value).map_err(|e| e.to_string())?,
+                width: None,
+                height: None,
+                gems: None,
+                swaps: None,
+            }
+        } else {
+            serde_json::from_value::<JsonBoard>(value).map_err(|e| format!("Invalid board: {e}"))?
+        };
+        let count = json.tiles.len();
+        let (width, height) = match (json.width, json.height) {
+            (Some(width), Some(height)) => (width, height),
+            (Some(width), None) => (width, count / width.max(1)),
+            (None, Some(height)) => (count / height.max(1), height),
+            (None, None) => {
+                let side = (count as f64).sqrt().round() as usize;
+                if side * side != count {
+                    return Err(format!(
+                        "Expected square number of tiles (e.g. 25 for 5x5), but got {count}"
+                    ));
+                }
+                (side, side)
+            }
+        };
+        let mut tiles = vec![];
+        for (index, value) in json.tiles.into_iter().enumerate() {
+            let tile_error = |e: String| {
+                let name = if width == 0 || width > 26 {
+                    String::from("?")
+                } else {
+                    format!(
+                        "{}{}",
+                        (b'A' + (index % width) as u8) as char,
+                        index / width + 1
+                    )
+                };
+                format!("{name} (tile {index}): {e}")
+            };
+            let tile: JsonTile =
+                serde_json::from_value(value).map_err(|e| tile_error(e.to_string()))?;
+            let mut chars = tile.letter.chars();
+            let letter = match (chars.next(), chars.next()) {
+                (Some(letter), None) if letter.is_ascii_alphabetic() || letter == '?' => {
+                    letter.to_ascii_lowercase()
+                }
+                _ => {
+                    return Err(tile_error(format!(
+                        "Expected single letter or ? as letter, but got {:?}",
+                        tile.letter
+                    )))
+                }
+            };
+            if !(1..=3).contains(&tile.letter_multiplier) {
+                return Err(tile_error(format!(
+                    "Expected letter multiplier to be 1-3, but got {}",
+                    tile.letter_multiplier
+                )));
+            }
+            if !(1..=9).contains(&tile.word_multiplier) {
+                return Err(tile_error(format!(
+                    "Expected word multiplier to be 1-9, but got {}",
+                    tile.word_multiplier
+                )));
+            }
+            tiles.push(Tile {
+                letter,
+                letter_multiplier: tile.letter_multiplier,
+                word_multiplier: tile.word_multiplier,
+                gem: tile.gem,
+                frozen: tile.frozen,
+            });
+        }
+        let swaps = match (json.swaps, json.gems) {
+            (Some(swaps), Some(gems)) if swaps as u16 * 3 > gems as u16 => {
+                return Err(format!(
+                    "Expected at least {} gems for {swaps} swaps, but got {gems}",
+                    swaps as u16 * 3
+                ))
+            }
+            (Some(swaps), _) => Some(swaps),
+            (None, Some(gems)) => Some(gems / 3),
+            (None, None) => None,
+        };
+        Ok((Board::with_size(tiles, width, height)?, swaps))
     }
 }
 
@@ -95,85 +721,259 @@ impl FromStr for Board {
 #[derive(Clone, Debug)]
 pub enum Step {
     /// Use tile @ `self.index` as is.
-    Normal { index: i8 },
+    Normal { index: u16 },
     /// Swap tile @ `self.index` to `self.new_letter`, then use.
-    Swap { index: i8, new_letter: char },
+    Swap { index: u16, new_letter: char },
+    /// Use wildcard tile @ `self.index` as `self.letter`.
+    /// Unlike swap, it doesn't cost anything.
+    Wildcard { index: u16, letter: char },
+}
+
+impl serde::Serialize for Step {
+    /// Serialises step as `{"swap":bool,"index":u16}` with `"new_letter"` for swaps and `"wildcard":true` + `"new_letter"` for wildcards.
+    /// This keeps JSON output flat and compatible with its original format.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("Step", 4)?;
+        match self {
+            Step::Normal { index } => {
+                state.serialize_field("swap", &false)?;
+                state.serialize_field("index", index)?;
+            }
+            Step::Swap { index, new_letter } => {
+                state.serialize_field("swap", &true)?;
+                state.serialize_field("index", index)?;
+                state.serialize_field("new_letter", new_letter)?;
+            }
+            Step::Wildcard { index, letter } => {
+                state.serialize_field("swap", &false)?;
+                state.serialize_field("wildcard", &true)?;
+                state.serialize_field("index", index)?;
+                state.serialize_field("new_letter", letter)?;
+            }
+        }
+        state.end()
+    }
 }
 
 impl Step {
     /// Returns `self.index`.
     /// Just a convenience function that handles matching and dereferencing.
-    pub fn index(&self) -> i8 {
+    pub fn index(&self) -> u16 {
         match self {
             Self::Normal { index } => *index,
             Self::Swap { index, .. } => *index,
+            Self::Wildcard { index, .. } => *index,
         }
     }
 
     /// Returns the letter represented by this Step.
     /// For Normal, it's letter from board tile @ `self.index`.
     /// For Swap, it's `self.new_letter`.
+    /// For Wildcard, it's `self.letter`.
     pub fn letter(&self, board: &Board) -> char {
         match self {
             Self::Normal { index } => board.tiles[*index as usize].letter,
             Self::Swap { new_letter, .. } => *new_letter,
+            Self::Wildcard { letter, .. } => *letter,
         }
     }
 }
 
-/// Struct that stores sequence of steps needed to form the word and word metadata.
-pub struct Word {
+/// Step in JSON format (same as in `steps` array of JSON output), see [Step::list_from_json].
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonStep {
+    #[serde(default)]
+    swap: bool,
+    #[serde(default)]
+    wildcard: bool,
+    index: u16,
+    new_letter: Option<char>,
+}
+
+impl Step {
+    /// Parses steps in the same format as `steps` array of JSON output, e.g. `[{"index":0},{"swap":true,"index":6,"new_letter":"e"}]`.
+    /// Whole word object from JSON output is accepted too (only its `steps` are used), so words can be copied as is.
+    /// Letters are case-insensitive. Steps aren't checked against any board, use [verify] for that.
+    pub fn list_from_json(s: &str) -> Result<Vec<Step>, String> {
+        let mut value: serde_json::Value =
+            serde_json::from_str(s).map_err(|e| format!("Invalid JSON: {e}"))?;
+        if let Some(steps) = value.get_mut("steps") {
+            value = steps.take();
+        }
+        let steps: Vec<JsonStep> =
+            serde_json::from_value(value).map_err(|e| format!("Invalid steps: {e}"))?;
+        steps
+            .into_iter()
+            .enumerate()
+            .map(|(position, step)| {
+                let index = step.index;
+                match (step.swap, step.wildcard, step.new_letter) {
+                    (false, false, None) => Ok(Step::Normal { index }),
+                    (true, false, Some(new_letter)) => Ok(Step::Swap {
+                        index,
+                        new_letter: new_letter.to_ascii_lowercase(),
+                    }),
+                    (false, true, Some(letter)) => Ok(Step::Wildcard {
+                        index,
+                        letter: letter.to_ascii_lowercase(),
+                    }),
+                    (true, true, _) => Err(format!(
+                        "Step {position}: step can't be both swap and wildcard"
+                    )),
+                    (false, false, Some(_)) => Err(format!(
+                        "Step {position}: new_letter is only allowed for swaps and wildcards"
+                    )),
+                    (_, _, None) => Err(format!(
+                        "Step {position}: swaps and wildcards need new_letter"
+                    )),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Points given for a single letter of the word.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LetterScore {
+    pub letter: char,
+    /// Index of the tile letter is on.
+    pub index: u16,
+    /// Base points of the letter (see [get_letter_points]).
+    pub points: u8,
+    /// Letter multiplier of the tile (1 if there is none).
+    pub multiplier: u8,
+}
+
+/// Explanation of how score and gems of the word are calculated.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ScoreBreakdown {
+    /// Points for each letter, only filled in by [Word::breakdown].
+    pub letters: Vec<LetterScore>,
+    /// Sum of letter points with letter multipliers applied.
+    pub letter_points: u32,
+    /// Word multiplier after combining all word multipliers (see [WordMultiplierRule]).
+    pub word_multiplier: u32,
+    /// Bonus for words that are at least 6 letters long.
+    pub long_word_bonus: u8,
     pub gems_collected: u8,
-    pub score: u16, // Using u16 for score just in case of some miracle overflow.
-    pub sorting_score: i32,
-    pub steps: Vec<Step>,
+    /// Gems spent on swaps (3 per swap).
+    pub gems_spent: u16,
+    #[serde(skip)]
     pub swaps_used: u8,
+    /// Final score, i.e. `letter_points * word_multiplier + long_word_bonus`.
+    pub score: u16,
 }
 
-impl Word {
-    /// Calculates score and metadata for sequence of steps and returns new instance of Word.
-    fn new(steps: Vec<Step>, board: &Board) -> Word {
-        let mut gems_collected = 0;
-        let mut score = 0;
-        let mut swaps_used = 0;
-        let mut word_multiplier = 1;
-        for step in &steps {
+impl ScoreBreakdown {
+    /// Calculates score and gems for sequence of steps.
+    /// `letters` is only filled in when `with_letters` is true, so [Word::new] doesn't have to allocate anything.
+    fn new(steps: &[Step], board: &Board, with_letters: bool) -> ScoreBreakdown {
+        let mut breakdown = ScoreBreakdown {
+            letters: vec![],
+            letter_points: 0,
+            word_multiplier: 1,
+            long_word_bonus: 0,
+            gems_collected: 0,
+            gems_spent: 0,
+            swaps_used: 0,
+            score: 0,
+        };
+        for step in steps {
             let tile = &board.tiles[step.index() as usize];
-            score += (get_letter_points(step.letter(board)) * tile.letter_multiplier) as u16;
-            word_multiplier = word_multiplier.max(tile.word_multiplier as u16);
+            let letter = step.letter(board);
+            let points = get_letter_points(letter);
+            breakdown.letter_points += (points * tile.letter_multiplier) as u32;
+            breakdown.word_multiplier = board
+                .word_multiplier_rule
+                .combine(breakdown.word_multiplier, tile.word_multiplier);
             if tile.gem {
-                gems_collected += 1;
+                breakdown.gems_collected += 1;
             }
             if matches!(step, Step::Swap { .. }) {
-                swaps_used += 1;
+                breakdown.swaps_used += 1;
+                breakdown.gems_spent += 3;
+            }
+            if with_letters {
+                breakdown.letters.push(LetterScore {
+                    letter,
+                    index: step.index(),
+                    points,
+                    multiplier: tile.letter_multiplier,
+                });
             }
         }
-        score *= word_multiplier;
         if steps.len() >= 6 {
-            score += 10;
+            breakdown.long_word_bonus = 10;
         }
+        // Score can only get this high with stacking multipliers, which aren't in the actual game anyway.
+        breakdown.score = breakdown
+            .letter_points
+            .saturating_mul(breakdown.word_multiplier)
+            .saturating_add(breakdown.long_word_bonus as u32)
+            .min(u16::MAX as u32) as u16;
+        breakdown
+    }
+
+    /// Returns value [SortedWordVec] sorts words by.
+    fn sorting_score(&self) -> i64 {
+        // Square the score so other things do not disrupt basic sorting.
+        (self.score as i64).pow(2)
+        // Net gems (collected gems - gems spent on swaps).
+        + (self.gems_collected as i64 - self.gems_spent as i64)
+    }
+}
+
+/// Struct that stores sequence of steps needed to form the word and word metadata.
+#[derive(Clone, serde::Serialize)]
+pub struct Word {
+    pub gems_collected: u8,
+    pub score: u16, // Using u16 for score just in case of some miracle overflow.
+    #[serde(skip)]
+    pub sorting_score: i64,
+    pub steps: Vec<Step>,
+    pub swaps_used: u8,
+}
+
+impl Word {
+    /// Calculates score and metadata for sequence of steps and returns new instance of Word.
+    pub fn new(steps: Vec<Step>, board: &Board) -> Word {
+        let breakdown = ScoreBreakdown::new(&steps, board, false);
+        Word::from_breakdown(steps, &breakdown)
+    }
+
+    /// Creates Word from steps and their already calculated breakdown.
+    fn from_breakdown(steps: Vec<Step>, breakdown: &ScoreBreakdown) -> Word {
         Word {
-            gems_collected,
-            score,
-            sorting_score: (
-                // Square the score so other things do not disrupt basic sorting.
-                (score as i32).pow(2)
-                // Net gems (collected gems - gems spent on swaps).
-                + (gems_collected as i32 - (swaps_used * 3) as i32)
-            ),
+            gems_collected: breakdown.gems_collected,
+            score: breakdown.score,
+            sorting_score: breakdown.sorting_score(),
             steps,
-            swaps_used,
+            swaps_used: breakdown.swaps_used,
         }
     }
 
+    /// Returns full explanation of word's score (including points for each letter).
+    pub fn breakdown(&self, board: &Board) -> ScoreBreakdown {
+        ScoreBreakdown::new(&self.steps, board, true)
+    }
+
     /// Returns actual word string.
     pub fn word(&self, board: &Board, show_swaps: bool) -> String {
         let mut buf = String::new();
         for step in &self.steps {
             if show_swaps {
-                if let Step::Swap { new_letter, .. } = step {
-                    buf += &format!("{}{}{}", RED, new_letter, RESET);
-                    continue;
+                match step {
+                    Step::Swap { new_letter, .. } => {
+                        buf += &format!("{}{}{}", RED, new_letter, RESET);
+                        continue;
+                    }
+                    Step::Wildcard { letter, .. } => {
+                        buf += &format!("{}{}{}", YELLOW, letter, RESET);
+                        continue;
+                    }
+                    Step::Normal { .. } => (),
                 }
             }
             buf += &step.letter(board).to_string();
@@ -182,117 +982,823 @@ impl Word {
     }
 }
 
-/// Wrapper for Vec<Word> that keeps only [crate::utils::MAX_SOLUTIONS] highest value items and is always sorted.
-struct SortedWordVec {
+/// Wrapper for Vec<Word> that keeps only `self.limit` (usually [crate::utils::MAX_SOLUTIONS]) highest value items and is always sorted.
+/// If created with [SortedWordVec::unique], it also keeps word strings of items in `self.keys` and never stores the same word twice.
+pub struct SortedWordVec {
     inner: Vec<Word>,
+    keys: Option<Vec<String>>,
+    limit: usize,
+}
+
+impl Default for SortedWordVec {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SortedWordVec {
     /// Creates empty SortedWordVec.
-    fn new() -> SortedWordVec {
+    pub fn new() -> SortedWordVec {
         SortedWordVec {
             inner: Vec::with_capacity(MAX_SOLUTIONS + 1), // Add 1 because it temporary exceeds limit by 1 inside self.push.
+            keys: None,
+            limit: MAX_SOLUTIONS,
         }
     }
 
-    /// Inserts value into inner Vec into position determined by binary search.
-    /// If it becomes longer than [crate::utils::MAX_SOLUTIONS], last item (with smallest value) is popped.
-    /// After function returns, `self.inner` is guaranteed to be sorted and <= [crate::utils::MAX_SOLUTIONS] in length.
-    fn push(&mut self, value: Word) {
+    /// Creates empty SortedWordVec that removes duplicate words and keeps only `limit` items.
+    /// Since there are no duplicates, `limit` can be as low as number of words actually needed.
+    pub fn unique(limit: usize) -> SortedWordVec {
+        let limit = limit.clamp(1, MAX_SOLUTIONS);
+        SortedWordVec {
+            inner: Vec::with_capacity(limit + 1),
+            keys: Some(Vec::with_capacity(limit + 1)),
+            limit,
+        }
+    }
+
+    /// Returns whether SortedWordVec holds `self.limit` items, so any new item has to beat the last one.
+    fn is_full(&self) -> bool {
+        self.inner.len() >= self.limit
+    }
+
+    /// Returns whether unique SortedWordVec could store item with `sorting_score`, i.e. it isn't full or item beats the last one.
+    /// It lets [solver] skip creating [Word] for most words it finds.
+    fn admits(&self, sorting_score: i64) -> bool {
+        !self.is_full() || self.inner[self.limit - 1].sorting_score < sorting_score
+    }
+
+    /// Returns position in `inner` where item with `sorting_score` should be inserted, determined by binary search.
+    fn position(inner: &[Word], sorting_score: i64) -> usize {
         let mut l = 0;
-        let mut r = self.inner.len();
+        let mut r = inner.len();
         let mut m;
         while l < r {
             m = (l + r) / 2;
-            if self.inner[m].sorting_score > value.sorting_score {
+            if inner[m].sorting_score > sorting_score {
                 l = m + 1;
-            } else if self.inner[m].sorting_score == value.sorting_score {
+            } else if inner[m].sorting_score == sorting_score {
                 l = m;
                 break;
             } else {
                 r = m;
             }
         }
+        l
+    }
+
+    /// Inserts value into inner Vec into position determined by binary search.
+    /// If it becomes longer than `self.limit`, last item (with smallest value) is popped.
+    /// For unique SortedWordVec, value is only inserted if the same word isn't already stored with higher or equal value.
+    /// After function returns, `self.inner` is guaranteed to be sorted and <= `self.limit` in length.
+    pub fn push(&mut self, value: Word, board: &Board) {
+        if self.keys.is_some() && !self.admits(value.sorting_score) {
+            return;
+        }
+        if let Some(keys) = &mut self.keys {
+            let key = value.word(board, false);
+            if let Some(existing) = keys.iter().position(|x| *x == key) {
+                if self.inner[existing].sorting_score >= value.sorting_score {
+                    return;
+                }
+                keys.remove(existing);
+                self.inner.remove(existing);
+            }
+            let l = SortedWordVec::position(&self.inner, value.sorting_score);
+            keys.insert(l, key);
+            self.inner.insert(l, value);
+            if self.inner.len() > self.limit {
+                keys.pop();
+                self.inner.pop();
+            }
+            return;
+        }
+        let l = SortedWordVec::position(&self.inner, value.sorting_score);
         self.inner.insert(l, value);
-        if self.inner.len() > MAX_SOLUTIONS {
+        if self.inner.len() > self.limit {
             self.inner.pop();
         }
     }
 }
 
+/// Upper bounds of what can still be added to the word after reaching dictionary node, see [Pruning].
+#[derive(Clone, Copy)]
+struct Bound {
+    /// Highest sum of letter points (without multipliers).
+    /// It is `u16`, because letters of long words (on boards bigger than 5x5) can add up to more than `u8::MAX`.
+    points: u16,
+    /// Highest number of letters.
+    length: u16,
+    /// Highest points of a single letter.
+    letter: u8,
+}
+
+impl Bound {
+    /// No word can be finished.
+    const UNREACHABLE: Bound = Bound {
+        points: u16::MAX,
+        length: 0,
+        letter: 0,
+    };
+    /// Word can end right here.
+    const END: Bound = Bound {
+        points: 0,
+        length: 0,
+        letter: 0,
+    };
+
+    fn is_reachable(&self) -> bool {
+        self.points != u16::MAX
+    }
+}
+
+/// Data used by [deep_solver] to prune branches during deep swap search (more than [crate::utils::MAX_FAST_SWAPS] swaps).
+/// With that many swaps almost every word in dictionary can be formed, so search has to be cut short where possible.
+/// Every dictionary node gets an index: children of a node are stored next to each other (in the same order as in its `next_letters`) starting at `self.children[index]`, and nodes of the dictionary itself start at 0.
+struct Pruning {
+    /// Index of the first child of every node (0 for `Word`).
+    children: Vec<u32>,
+    /// Rows of `self.width` values for every node, where value N bounds what can still be added to the word after reaching node,
+    /// using at most N letters that aren't on board (i.e. have to be swapped or taken from wildcard tile).
+    bounds: Vec<Bound>,
+    width: usize,
+}
+
+impl Pruning {
+    /// Walks the whole dictionary tree and creates new instance of Pruning for specific board (only `usable` tiles) and swap count.
+    fn new(dictionary: &[(char, Node)], board: &Board, usable: &[bool], swaps: u8) -> Pruning {
+        let mut on_board = [false; 26];
+        let mut wildcards = 0;
+        for (_, tile) in board.tiles.iter().enumerate().filter(|x| usable[x.0]) {
+            if tile.letter == '?' {
+                wildcards += 1;
+            } else {
+                on_board[(tile.letter as u8 - b'a') as usize] = true;
+            }
+        }
+        let width = (swaps as usize + wildcards + 1).min(26);
+        let mut pruning = Pruning {
+            children: vec![0; dictionary.len()],
+            bounds: vec![Bound::END; dictionary.len() * width],
+            width,
+        };
+        pruning.collect(dictionary, 0, &on_board);
+        pruning
+    }
+
+    /// Recursively fills rows for `next_letters` (stored from index `first`) and their children and returns row of their parent.
+    fn collect(
+        &mut self,
+        next_letters: &[(char, Node)],
+        first: usize,
+        on_board: &[bool; 26],
+    ) -> [Bound; 26] {
+        let mut best = [Bound::UNREACHABLE; 26];
+        for (offset, (letter, node)) in next_letters.iter().enumerate() {
+            let index = first + offset;
+            let after = match node {
+                Node::Word => [Bound::END; 26],
+                Node::Both { next_letters } | Node::Prefix { next_letters } => {
+                    let children = self.children.len();
+                    self.children[index] = children as u32;
+                    self.children.resize(children + next_letters.len(), 0);
+                    self.bounds
+                        .resize((children + next_letters.len()) * self.width, Bound::END);
+                    let mut after = self.collect(next_letters, children, on_board);
+                    if matches!(node, Node::Both { .. }) {
+                        // Word can also end right here.
+                        for bound in after.iter_mut().filter(|x| !x.is_reachable()) {
+                            *bound = Bound::END;
+                        }
+                    }
+                    self.bounds[index * self.width..(index + 1) * self.width]
+                        .copy_from_slice(&after[..self.width]);
+                    after
+                }
+            };
+            let cost = if letter.is_ascii_lowercase() && on_board[(*letter as u8 - b'a') as usize] {
+                0
+            } else {
+                1
+            };
+            let points = get_letter_points(*letter);
+            for budget in cost..self.width {
+                let rest = after[budget - cost];
+                if !rest.is_reachable() {
+                    continue;
+                }
+                let best = &mut best[budget];
+                if !best.is_reachable() {
+                    *best = Bound::END;
+                }
+                best.points = best.points.max(points as u16 + rest.points);
+                best.length = best.length.max(rest.length + 1);
+                best.letter = best.letter.max(points).max(rest.letter);
+            }
+        }
+        best
+    }
+
+    /// Returns whether any word continuing `state.steps` through `node` (with `index`) with `swaps` remaining can still make it into `words`.
+    /// It compares optimistic estimate of its sorting score with the worst word stored: only as many letters as node and unused tiles allow are added,
+    /// and they get the best unused letter and word multipliers and gems.
+    fn is_promising(
+        &self,
+        search: &Search,
+        state: &DeepState,
+        node: &Node,
+        index: u32,
+        swaps: u8,
+        words: &SortedWordVec,
+    ) -> bool {
+        if !words.is_full() {
+            return true;
+        }
+        let budget = (swaps as usize + state.free_wildcards as usize).min(self.width - 1);
+        let bound = self.bounds[index as usize * self.width + budget];
+        if !bound.is_reachable() {
+            return false;
+        }
+        let length = state.steps.len();
+        let remaining = (bound.length as usize)
+            .min(state.free_tiles as usize)
+            .min(search.max_length.saturating_sub(length));
+        if remaining == 0 && matches!(node, Node::Prefix { .. }) {
+            return false;
+        }
+        // Each letter can be at most tripled, and only as many letters as there are multiplier tiles get multiplied.
+        let [_, _, doubled, tripled] = state.free_letter_multipliers.map(|x| x as usize);
+        let tripled = tripled.min(remaining);
+        let doubled = doubled.min(remaining - tripled);
+        let letter_bonus = (bound.letter as u32 * (2 * tripled + doubled) as u32).min(
+            bound.points as u32
+                * match (tripled, doubled) {
+                    (0, 0) => 0,
+                    (0, _) => 1,
+                    _ => 2,
+                },
+        );
+        let rule = search.board.word_multiplier_rule;
+        let mut word_multiplier = state.word_multiplier;
+        let mut multipliers = remaining;
+        for (multiplier, count) in state.free_word_multipliers.iter().enumerate().rev() {
+            if multipliers == 0 || multiplier <= 1 {
+                break;
+            }
+            for _ in 0..(*count as usize).min(multipliers) {
+                word_multiplier = rule.combine(word_multiplier, multiplier as u8);
+                multipliers -= 1;
+            }
+        }
+        let long_word_bonus = if length + remaining >= 6 { 10 } else { 0 };
+        let score = ((state.letter_points + bound.points as u32 + letter_bonus) as u64
+            * word_multiplier as u64
+            + long_word_bonus)
+            .min(u16::MAX as u64) as i64;
+        let gems = state.gems as i64 + (state.free_gems as usize).min(remaining) as i64;
+        let gems_spent = 3 * (search.swaps - swaps) as i64;
+        words.admits(score.pow(2) + gems - gems_spent)
+    }
+}
+
+/// Mutable state of [deep_solver], updated as steps are added and removed, so [Pruning::is_promising] doesn't have to go through all steps and tiles at every node.
+struct DeepState<'a> {
+    steps: Vec<Step>,
+    /// Whether tile is used by `self.steps`.
+    used: Vec<bool>,
+    /// Sum of letter points of `self.steps` with letter multipliers applied.
+    letter_points: u32,
+    /// Word multiplier of `self.steps` (see [WordMultiplierRule]).
+    word_multiplier: u32,
+    /// Gems collected by `self.steps`.
+    gems: u8,
+    /// Number of usable tiles that aren't used yet with each letter multiplier (index is the multiplier).
+    free_letter_multipliers: [u16; 4],
+    /// Number of usable tiles that aren't used yet with each word multiplier (index is the multiplier).
+    free_word_multipliers: [u16; 10],
+    free_gems: u16,
+    free_wildcards: u16,
+    free_tiles: u16,
+    poll: Poll<'a>,
+}
+
+impl<'a> DeepState<'a> {
+    /// Creates state for initial [deep_solver] call with `steps`.
+    fn new(search: &Search, steps: Vec<Step>, poll: Poll<'a>) -> DeepState<'a> {
+        let mut state = DeepState {
+            steps: vec![],
+            used: vec![false; search.board.tiles.len()],
+            letter_points: 0,
+            word_multiplier: 1,
+            gems: 0,
+            free_letter_multipliers: [0; 4],
+            free_word_multipliers: [0; 10],
+            free_gems: 0,
+            free_wildcards: 0,
+            free_tiles: 0,
+            poll,
+        };
+        for (index, tile) in search.board.tiles.iter().enumerate() {
+            if search.usable[index] {
+                state.count(tile, 1);
+            }
+        }
+        for step in steps {
+            state.push(step, search.board);
+        }
+        state
+    }
+
+    /// Adds `change` to counts of free tiles for `tile`.
+    fn count(&mut self, tile: &Tile, change: i16) {
+        let add = |count: &mut u16| *count = count.wrapping_add_signed(change);
+        add(&mut self.free_letter_multipliers[tile.letter_multiplier as usize]);
+        add(&mut self.free_word_multipliers[tile.word_multiplier as usize]);
+        if tile.gem {
+            add(&mut self.free_gems);
+        }
+        if tile.letter == '?' {
+            add(&mut self.free_wildcards);
+        }
+        add(&mut self.free_tiles);
+    }
+
+    /// Adds step and returns word multiplier from before it, which has to be passed to [DeepState::pop].
+    fn push(&mut self, step: Step, board: &Board) -> u32 {
+        let tile = &board.tiles[step.index() as usize];
+        let word_multiplier = self.word_multiplier;
+        self.used[step.index() as usize] = true;
+        self.letter_points +=
+            (get_letter_points(step.letter(board)) * tile.letter_multiplier) as u32;
+        self.word_multiplier = board
+            .word_multiplier_rule
+            .combine(word_multiplier, tile.word_multiplier);
+        self.gems += tile.gem as u8;
+        self.count(tile, -1);
+        self.steps.push(step);
+        word_multiplier
+    }
+
+    /// Removes the last step.
+    fn pop(&mut self, board: &Board, word_multiplier: u32) {
+        let step = self
+            .steps
+            .pop()
+            .expect("`steps` should have at least one item");
+        let tile = &board.tiles[step.index() as usize];
+        self.used[step.index() as usize] = false;
+        self.letter_points -=
+            (get_letter_points(step.letter(board)) * tile.letter_multiplier) as u32;
+        self.word_multiplier = word_multiplier;
+        self.gems -= tile.gem as u8;
+        self.count(tile, 1);
+    }
+}
+
+/// Options that control which words [solver_wrapper] looks for.
+/// Tile constraints are enforced during the search, so words that don't satisfy them never take up space in [SortedWordVec].
+/// All tile indices have to be valid for the board (see [Board::parse_tiles]).
+#[derive(Debug, Default, serde::Serialize)]
+pub struct SolveOptions {
+    /// Tiles that have to be used by the word.
+    pub include: Vec<u16>,
+    /// Tiles that can't be used by the word (as if they were frozen).
+    pub exclude: Vec<u16>,
+    /// Tiles word can start on. Any tile if empty.
+    pub start: Vec<u16>,
+    /// Tiles word can end on. Any tile if empty.
+    pub end: Vec<u16>,
+    /// Minimum word length.
+    pub min_length: u8,
+    /// Maximum word length. No limit if `None`.
+    pub max_length: Option<u8>,
+    /// Regexes that every word has to match (see [pattern_to_regex] for simpler patterns).
+    #[serde(serialize_with = "serialize_regexes")]
+    pub patterns: Vec<Regex>,
+    /// Words that can be used. Any dictionary word if `None`.
+    /// It isn't serialised, because it can be as big as the dictionary itself.
+    #[serde(skip)]
+    pub word_list: Option<HashSet<String>>,
+}
+
+impl SolveOptions {
+    /// Returns whether options restrict words themselves, i.e. dictionary has to be filtered with [SolveOptions::accepts_word].
+    fn filters_words(&self) -> bool {
+        !self.patterns.is_empty() || self.word_list.is_some()
+    }
+
+    /// Returns whether `word` matches all patterns and is in word list.
+    fn accepts_word(&self, word: &str) -> bool {
+        self.patterns.iter().all(|x| x.is_match(word))
+            && self.word_list.as_ref().is_none_or(|x| x.contains(word))
+    }
+}
+
+/// Serialises regexes as their source strings.
+fn serialize_regexes<S: serde::Serializer>(
+    regexes: &[Regex],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(regexes.iter().map(|x| x.as_str()))
+}
+
+/// Compiles case-insensitive regex that is used to filter words.
+pub fn compile_regex(regex: &str) -> Result<Regex, String> {
+    RegexBuilder::new(regex)
+        .case_insensitive(true)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Converts simple pattern into regex that matches whole word.
+/// `*` stands for any number of letters (including none) and `?` stands for exactly one letter, other characters are matched literally.
+/// For example, `*qu*` matches words containing "qu" and `*ing` matches words ending in "ing".
+pub fn pattern_to_regex(pattern: &str) -> Result<Regex, String> {
+    let mut regex = String::from("^");
+    for char in pattern.chars() {
+        match char {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            _ => regex.push_str(&regex::escape(&char.to_string())),
+        }
+    }
+    regex.push('$');
+    compile_regex(&regex)
+}
+
+/// Callback that receives best words found so far, see [SolveControl::progress].
+pub type Progress<'a> = dyn Fn(&[Word]) + Sync + 'a;
+
+/// Runtime control over single [solver_wrapper] call, used by long-running servers.
+#[derive(Default, Clone, Copy)]
+pub struct SolveControl<'a> {
+    /// Search stops as soon as possible once it is set, returned words are incomplete then.
+    pub cancel: Option<&'a AtomicBool>,
+    /// Called with best words found so far (unique, up to move count) whenever they change during the search.
+    /// It is called after each initial [solver] call at most, so it doesn't slow the search down much.
+    pub progress: Option<&'a Progress<'a>>,
+    /// Thread pool that multithreaded search runs on, so long-running servers don't spawn new threads for every solve.
+    /// Without it, each call spawns its own threads.
+    pub pool: Option<&'a rayon_core::ThreadPool>,
+    /// Search stops once this time is reached (same as with [SolveControl::cancel]), so deep swap search can't run indefinitely.
+    pub deadline: Option<Instant>,
+}
+
+impl SolveControl<'_> {
+    /// Returns whether search should stop, because it was cancelled or it ran out of time.
+    fn stopped(&self) -> bool {
+        self.cancel.is_some_and(|x| x.load(Ordering::Relaxed))
+            || self.deadline.is_some_and(|x| Instant::now() >= x)
+    }
+}
+
+/// Returns up to `move_count` best words from sorted `words`, skipping duplicates (same word string) of better words.
+fn top_unique<'a>(words: &'a [Word], board: &Board, move_count: u8) -> Vec<&'a Word> {
+    let mut existing_words = vec![];
+    let mut final_words = vec![];
+    for word in words {
+        if final_words.len() >= move_count as usize {
+            break;
+        }
+        let word_str = word.word(board, false);
+        if existing_words.contains(&word_str) {
+            continue;
+        }
+        existing_words.push(word_str);
+        final_words.push(word);
+    }
+    final_words
+}
+
+/// Checks [SolveControl::cancel] and [SolveControl::deadline] every [Poll::INTERVAL] nodes, so [solver] doesn't check them at every node.
+/// Once search is cancelled, it stays cancelled and the rest of the branch returns right away.
+struct Poll<'a> {
+    control: SolveControl<'a>,
+    /// Nodes visited since the start of initial call.
+    nodes: u32,
+    cancelled: bool,
+}
+
+impl<'a> Poll<'a> {
+    const INTERVAL: u32 = 1024;
+
+    fn new(control: SolveControl<'a>) -> Poll<'a> {
+        Poll {
+            control,
+            nodes: 0,
+            cancelled: false,
+        }
+    }
+
+    /// Counts visited node and returns whether search is cancelled.
+    fn cancelled(&mut self) -> bool {
+        self.nodes = self.nodes.wrapping_add(1);
+        if self.nodes.is_multiple_of(Poll::INTERVAL) {
+            self.cancelled = self.control.stopped();
+        }
+        self.cancelled
+    }
+}
+
+/// Read-only state shared by all [solver] calls during single [solver_wrapper] call.
+struct Search<'a> {
+    board: &'a Board,
+    options: &'a SolveOptions,
+    /// Whether tile can be used at all (i.e. it isn't frozen or excluded).
+    usable: Vec<bool>,
+    /// Whether word can end on tile.
+    can_end: Vec<bool>,
+    /// Maximum word length (number of tiles if there is no such option).
+    max_length: usize,
+    /// Whether options restrict tiles or length of words, or search can be stopped early (cancelled or out of time).
+    /// Otherwise [solver] skips all checks for them (see its `CHECKED` parameter).
+    checked: bool,
+    /// Swap count of the whole search.
+    swaps: u8,
+    /// Used for deep swap search, see [deep_solver].
+    pruning: Option<Pruning>,
+    /// See [SolveControl::cancel] and [SolveControl::deadline].
+    control: SolveControl<'a>,
+}
+
+impl Search<'_> {
+    /// Returns whether word formed by `steps` satisfies options that can only be checked once word is complete.
+    fn accepts(&self, steps: &[Step]) -> bool {
+        steps.len() >= self.options.min_length as usize
+            && self.can_end[steps[steps.len() - 1].index() as usize]
+            && self
+                .options
+                .include
+                .iter()
+                .all(|x| steps.iter().any(|step| step.index() == *x))
+    }
+
+    /// Runs initial call (first step, its dictionary node and index of the node, remaining swaps) with [solver] or [deep_solver].
+    fn run(&self, call: (Vec<Step>, &Node, u32, u8), words: &mut SortedWordVec) {
+        let (mut steps, node, index, swaps) = call;
+        let mut poll = Poll::new(self.control);
+        match &self.pruning {
+            Some(pruning) => {
+                let mut state = DeepState::new(self, steps, poll);
+                deep_solver(self, pruning, &mut state, node, index, swaps, words);
+            }
+            None if self.checked => solver::<true>(self, &mut steps, node, swaps, words, &mut poll),
+            None => solver::<false>(self, &mut steps, node, swaps, words, &mut poll),
+        }
+    }
+
+    /// Adds word formed by `steps` to `words` if it satisfies options (only checked if `CHECKED` is true).
+    /// Its steps are only cloned if its score is high enough to get in.
+    fn found<const CHECKED: bool>(&self, steps: &[Step], words: &mut SortedWordVec) {
+        if CHECKED && !self.accepts(steps) {
+            return;
+        }
+        let breakdown = ScoreBreakdown::new(steps, self.board, false);
+        if words.admits(breakdown.sorting_score()) {
+            words.push(Word::from_breakdown(steps.to_vec(), &breakdown), self.board);
+        }
+    }
+
+    /// Returns whether words continuing `steps` can still use all required tiles without exceeding maximum length.
+    fn can_include(&self, steps: &[Step]) -> bool {
+        let missing = self
+            .options
+            .include
+            .iter()
+            .filter(|x| !steps.iter().any(|step| step.index() == **x))
+            .count();
+        steps.len() + missing <= self.max_length
+    }
+}
+
 /// Recursively solves the board starting from `node`and adds found words to `words` [SortedWordVec].
 /// `steps` is used to avoid duplicate steps and determine current position on board.
 /// If `swaps` is not 0, additional calls with `swaps` reduced by 1 and `Step::Swap` for remaining next letters are created.
-fn solver(board: &Board, steps: &mut Vec<Step>, node: &Node, swaps: u8, words: &mut SortedWordVec) {
+/// Wildcard tiles get `Step::Wildcard` for every next letter without reducing `swaps`.
+/// Branches that can't satisfy [SolveOptions] are skipped.
+/// Options and cancellation are only checked if `CHECKED` is true, which is the case whenever [Search::checked] is.
+fn solver<const CHECKED: bool>(
+    search: &Search,
+    steps: &mut Vec<Step>,
+    node: &Node,
+    swaps: u8,
+    words: &mut SortedWordVec,
+    poll: &mut Poll,
+) {
+    let board = search.board;
+    if CHECKED {
+        if poll.cancelled() {
+            return;
+        }
+        if !search.options.include.is_empty() && !search.can_include(steps) {
+            return;
+        }
+    }
     let last_step = steps.last().expect("`steps` should have at least one item");
     let last_index = last_step.index();
-    let old_moves: Vec<i8> = steps.into_iter().map(|m| m.index()).collect();
-    let final_next_letters;
-    match node {
-        Node::Word => return words.push(Word::new(steps.clone(), board)),
+    let final_next_letters = match node {
+        Node::Word => {
+            search.found::<CHECKED>(steps, words);
+            return;
+        }
         Node::Both { next_letters } => {
-            words.push(Word::new(steps.clone(), board));
-            final_next_letters = next_letters;
+            search.found::<CHECKED>(steps, words);
+            next_letters
         }
-        Node::Prefix { next_letters } => final_next_letters = next_letters,
+        Node::Prefix { next_letters } => next_letters,
+    };
+    // Without the option, word can't get longer than number of tiles anyway.
+    if CHECKED && steps.len() >= search.max_length {
+        return;
     }
-    let x = last_index % 5;
-    let y = last_index / 5;
-    for dx in [-1, 0, 1] {
-        for dy in [-1, 0, 1] {
-            if dx == 0 && dy == 0 {
-                continue;
-            }
-            let nx = x + dx;
-            let ny = y + dy;
-            if nx < 0 || nx > 4 || ny < 0 || ny > 4 {
-                continue;
+    for &ni in &board.neighbors[last_index as usize] {
+        let tile_letter = board.tiles[ni as usize].letter;
+        if !search.usable[ni as usize] || steps.iter().any(|x| x.index() == ni) {
+            continue;
+        }
+        if tile_letter == '?' {
+            for (letter, sub_node) in final_next_letters {
+                steps.push(Step::Wildcard {
+                    index: ni,
+                    letter: *letter,
+                });
+                solver::<CHECKED>(search, steps, sub_node, swaps, words, poll);
+                steps.pop();
             }
-            let ni = ny * 5 + nx;
-            let tile = &board.tiles[ni as usize];
-            if tile.frozen || old_moves.contains(&ni) {
-                continue;
+        } else if swaps == 0 {
+            // Only the letter that is already on the tile can follow.
+            if let Some((_, sub_node)) = final_next_letters.iter().find(|x| x.0 == tile_letter) {
+                steps.push(Step::Normal { index: ni });
+                solver::<CHECKED>(search, steps, sub_node, swaps, words, poll);
+                steps.pop();
             }
+        } else {
             for (letter, sub_node) in final_next_letters {
-                if *letter == tile.letter {
+                if *letter == tile_letter {
                     steps.push(Step::Normal { index: ni });
-                    solver(board, steps, sub_node, swaps, words);
-                    steps.pop();
-                } else if swaps > 0 {
+                    solver::<CHECKED>(search, steps, sub_node, swaps, words, poll);
+                } else {
                     steps.push(Step::Swap {
                         index: ni,
                         new_letter: *letter,
                     });
-                    solver(board, steps, sub_node, swaps - 1, words);
-                    steps.pop();
+                    solver::<CHECKED>(search, steps, sub_node, swaps - 1, words, poll);
                 }
+                steps.pop();
             }
         }
     }
 }
 
+/// Deep swap search version of [solver], which also skips branches that can't beat words already found (see [Pruning]).
+/// `index` is index of `node` in `pruning`.
+fn deep_solver(
+    search: &Search,
+    pruning: &Pruning,
+    state: &mut DeepState,
+    node: &Node,
+    index: u32,
+    swaps: u8,
+    words: &mut SortedWordVec,
+) {
+    let board = search.board;
+    if state.poll.cancelled() {
+        return;
+    }
+    if !search.options.include.is_empty() && !search.can_include(&state.steps) {
+        return;
+    }
+    if !pruning.is_promising(search, state, node, index, swaps, words) {
+        return;
+    }
+    let next_letters = match node {
+        Node::Word => {
+            search.found::<true>(&state.steps, words);
+            return;
+        }
+        Node::Both { next_letters } => {
+            search.found::<true>(&state.steps, words);
+            next_letters
+        }
+        Node::Prefix { next_letters } => next_letters,
+    };
+    if state.steps.len() >= search.max_length {
+        return;
+    }
+    let children = pruning.children[index as usize];
+    let last_index = state.steps[state.steps.len() - 1].index();
+    for &ni in &board.neighbors[last_index as usize] {
+        let tile = &board.tiles[ni as usize];
+        if !search.usable[ni as usize] || state.used[ni as usize] {
+            continue;
+        }
+        for (offset, (letter, sub_node)) in next_letters.iter().enumerate() {
+            let (step, sub_swaps) = if *letter == tile.letter {
+                (Step::Normal { index: ni }, swaps)
+            } else if tile.letter == '?' {
+                let letter = *letter;
+                (Step::Wildcard { index: ni, letter }, swaps)
+            } else if swaps > 0 {
+                let new_letter = *letter;
+                (
+                    Step::Swap {
+                        index: ni,
+                        new_letter,
+                    },
+                    swaps - 1,
+                )
+            } else {
+                continue;
+            };
+            let word_multiplier = state.push(step, board);
+            let sub_index = children + offset as u32;
+            deep_solver(
+                search, pruning, state, sub_node, sub_index, sub_swaps, words,
+            );
+            state.pop(board, word_multiplier);
+        }
+    }
+}
+
 /// Wrapper that creates initial [solver] calls and handles multithreading.
-/// For each tile initial calls consist of `Step::Normal` for original letters and (if swaps are available) `Step::Swap` for rest of letters.
-/// It takes ownership of board because of how multithreading is implemented, but it is returned back alongside with solving results.
-/// Returned words are automatically sorted thanks to [SortedWordVec], duplicates are removed. only up to move_count moves are returned.
+/// For each tile (that word can start on) initial calls consist of `Step::Normal` for original letters and (if swaps are available) `Step::Swap` for rest of letters.
+/// Wildcard tiles get `Step::Wildcard` for every letter instead.
+/// Returned words are automatically sorted thanks to [SortedWordVec], duplicates are removed. Only up to move_count moves are returned.
+/// If options restrict words themselves (patterns or word list), search runs on filtered copy of the dictionary, so words that don't match don't even get visited.
+/// With `control`, search can be cancelled or limited in time and best words found so far are reported during the search (see [SolveControl]).
+/// Swap count is capped at number of tiles that can be used. If it is higher than [crate::utils::MAX_FAST_SWAPS], deep swap search ([deep_solver]) is used instead.
 pub fn solver_wrapper(
-    board: Board,
+    board: &Board,
     swaps: u8,
     thread_count: u8,
-    dictionary: &'static Vec<(char, Node)>,
+    dictionary: &Vec<(char, Node)>,
     move_count: u8,
-) -> (Vec<Word>, Board) {
+    options: &SolveOptions,
+    control: SolveControl,
+) -> Vec<Word> {
+    let filtered_dictionary;
+    let dictionary = if options.filters_words() {
+        filtered_dictionary =
+            filter_dictionary_tree(dictionary, &|word| options.accepts_word(word));
+        &filtered_dictionary
+    } else {
+        dictionary
+    };
+    let mut usable: Vec<bool> = board.tiles.iter().map(|x| !x.frozen).collect();
+    for index in &options.exclude {
+        usable[*index as usize] = false;
+    }
+    let mut can_end = vec![options.end.is_empty(); board.tiles.len()];
+    for index in &options.end {
+        can_end[*index as usize] = true;
+    }
+    let swaps = (swaps as usize).min(usable.iter().filter(|x| **x).count()) as u8;
+    let deep = swaps > MAX_FAST_SWAPS;
+    let search = Search {
+        board,
+        options,
+        swaps,
+        pruning: deep.then(|| Pruning::new(dictionary, board, &usable, swaps)),
+        usable,
+        can_end,
+        max_length: match options.max_length {
+            Some(max_length) => max_length as usize,
+            None => board.tiles.len(),
+        },
+        checked: !options.include.is_empty()
+            || !options.end.is_empty()
+            || options.min_length > 0
+            || options.max_length.is_some()
+            || control.cancel.is_some()
+            || control.deadline.is_some(),
+        control,
+    };
     let mut calls = vec![];
-    let mut words = SortedWordVec::new();
-    for (index, tile) in (&board.tiles).into_iter().enumerate() {
-        let index = index as i8;
-        if tile.frozen {
+    // Duplicates are removed as words are found, otherwise copies of a few best words (e.g. same word with swaps on different tiles) could crowd out all other words.
+    let new_words = || SortedWordVec::unique(move_count as usize);
+    let mut words = new_words();
+    for (index, tile) in board.tiles.iter().enumerate() {
+        if !search.usable[index]
+            || !(options.start.is_empty() || options.start.contains(&(index as u16)))
+        {
             continue;
         }
-        for (new_letter, node) in dictionary {
+        let index = index as u16;
+        for (node_index, (new_letter, node)) in dictionary.iter().enumerate() {
+            let node_index = node_index as u32;
             if *new_letter == tile.letter {
-                calls.push((vec![Step::Normal { index }], node, swaps));
+                calls.push((vec![Step::Normal { index }], node, node_index, swaps));
                 continue;
+            } else if tile.letter == '?' {
+                calls.push((
+                    vec![Step::Wildcard {
+                        index,
+                        letter: *new_letter,
+                    }],
+                    node,
+                    node_index,
+                    swaps,
+                ));
             } else if swaps > 0 {
                 calls.push((
                     vec![Step::Swap {
@@ -300,62 +1806,198 @@ pub fn solver_wrapper(
                         new_letter: *new_letter,
                     }],
                     node,
+                    node_index,
                     swaps - 1,
                 ));
             }
         }
     }
-    let (words, board) = {
-        if thread_count <= 1 {
-            for mut call in calls {
-                solver(&board, &mut call.0, call.1, call.2, &mut words);
+    if search.max_length == 0 {
+        calls.clear();
+    }
+    // Best words of each thread and keys (word string and sorting score) of words that were reported last.
+    type Snapshots = (Vec<Vec<Word>>, Vec<Vec<(String, i64)>>, Vec<(String, i64)>);
+    let snapshots: Mutex<Snapshots> = Mutex::new((
+        vec![vec![]; thread_count.max(1) as usize],
+        vec![vec![]; thread_count.max(1) as usize],
+        vec![],
+    ));
+    let keys = |words: &[&Word]| -> Vec<(String, i64)> {
+        words
+            .iter()
+            .map(|x| (x.word(board, false), x.sorting_score))
+            .collect()
+    };
+    // Updates snapshot of thread and reports merged best words if they changed.
+    let report = |thread: usize, thread_words: &SortedWordVec| {
+        let Some(progress) = control.progress else {
+            return;
+        };
+        let top = top_unique(&thread_words.inner, board, move_count);
+        let mut snapshots = snapshots.lock().unwrap_or_else(|e| e.into_inner());
+        let (thread_snapshots, thread_keys, last_keys) = &mut *snapshots;
+        let top_keys = keys(&top);
+        if thread_keys[thread] == top_keys {
+            return;
+        }
+        thread_keys[thread] = top_keys;
+        thread_snapshots[thread] = top.into_iter().cloned().collect();
+        let mut merged = SortedWordVec::new();
+        for word in thread_snapshots.iter().flatten() {
+            merged.push(word.clone(), board);
+        }
+        let merged = top_unique(&merged.inner, board, move_count);
+        let merged_keys = keys(&merged);
+        if *last_keys != merged_keys {
+            *last_keys = merged_keys;
+            progress(&merged.into_iter().cloned().collect::<Vec<_>>());
+        }
+    };
+    let cancelled = || control.stopped();
+    if thread_count <= 1 {
+        for call in calls {
+            if cancelled() {
+                break;
             }
-            (words.inner, board)
-        } else {
-            // Nope, won't be doing Arc (tested it, performance with Arc sucks).
-            // Unsafe code is completely safe, because all threads using board_ref are join()ed before taking back the board.
-            let board_ptr = Box::into_raw(Box::new(board));
-            let mut threads = vec![];
-            let chunk_size = (calls.len() + thread_count as usize - 1) / thread_count as usize;
-            {
-                let board_ref: &'static Board = unsafe { &*board_ptr };
-                while !calls.is_empty() {
-                    let chunk = calls
-                        .drain(..chunk_size.min(calls.len()))
-                        .collect::<Vec<_>>();
-                    threads.push(std::thread::spawn(move || {
-                        let mut thread_words = SortedWordVec::new();
-                        for mut call in chunk {
-                            solver(&board_ref, &mut call.0, call.1, call.2, &mut thread_words);
-                        }
-                        thread_words
-                    }))
+            search.run(call, &mut words);
+            report(0, &words);
+        }
+    } else {
+        // Nope, won't be doing Arc (tested it, performance with Arc sucks).
+        // Scoped threads can borrow search state directly and are all join()ed before scope ends.
+        let chunk_size = calls.len().div_ceil(thread_count as usize);
+        let mut chunks = vec![];
+        while !calls.is_empty() {
+            chunks.push(
+                calls
+                    .drain(..chunk_size.min(calls.len()))
+                    .collect::<Vec<_>>(),
+            );
+        }
+        let search = &search;
+        let report = &report;
+        let cancelled = &cancelled;
+        let run_chunk = move |thread: usize, chunk: Vec<_>| {
+            let mut thread_words = new_words();
+            for call in chunk {
+                if cancelled() {
+                    break;
                 }
-                for thread in threads {
-                    if let Ok(thread_words) = thread.join() {
-                        for word in thread_words.inner {
-                            words.push(word);
-                        }
+                search.run(call, &mut thread_words);
+                report(thread, &thread_words);
+            }
+            thread_words
+        };
+        // Results are merged in chunk order, so words with the same score are always in the same order.
+        let mut results: Vec<Option<SortedWordVec>> = vec![];
+        match control.pool {
+            Some(pool) => {
+                let slots: Vec<Mutex<Option<SortedWordVec>>> =
+                    chunks.iter().map(|_| Mutex::new(None)).collect();
+                pool.scope(|scope| {
+                    for ((thread, chunk), slot) in chunks.into_iter().enumerate().zip(&slots) {
+                        scope.spawn(move |_| {
+                            let thread_words = run_chunk(thread, chunk);
+                            *slot.lock().unwrap_or_else(|e| e.into_inner()) = Some(thread_words);
+                        });
                     }
-                }
+                });
+                results.extend(
+                    slots
+                        .into_iter()
+                        .map(|x| x.into_inner().unwrap_or_else(|e| e.into_inner())),
+                );
             }
-            (words.inner, unsafe { *Box::from_raw(board_ptr) })
+            None => std::thread::scope(|scope| {
+                let threads: Vec<_> = chunks
+                    .into_iter()
+                    .enumerate()
+                    .map(|(thread, chunk)| scope.spawn(move || run_chunk(thread, chunk)))
+                    .collect();
+                results.extend(threads.into_iter().map(|x| x.join().ok()));
+            }),
         }
-    };
-    let mut existing_words = vec![];
-    let mut counter = 0;
-    let mut final_words = vec![];
-    for word in words {
-        if counter >= move_count {
-            break;
+        for thread_words in results.into_iter().flatten() {
+            for word in thread_words.inner {
+                words.push(word, board);
+            }
         }
-        let word_str = word.word(&board, false);
-        if existing_words.contains(&word_str) {
-            continue;
+    }
+    top_unique(&words.inner, board, move_count)
+        .into_iter()
+        .cloned()
+        .collect()
+}
+
+/// Checks that `steps` are a valid move on the board with at most `swaps` swaps and returns it as [Word] (score and gems are recomputed with [Word::new]).
+/// Move has to follow the same rules as words found by [solver_wrapper]: each tile is adjacent to the previous one (see [Board::set_adjacency]), no tile is used twice, frozen tiles aren't used, swaps change letter of a non-wildcard tile, wildcard tiles are always used with a letter and the word is in dictionary.
+/// [SolveOptions] aren't checked. If move is invalid, the first problem found is returned.
+pub fn verify(
+    board: &Board,
+    steps: Vec<Step>,
+    swaps: u8,
+    dictionary: &[(char, Node)],
+) -> Result<Word, String> {
+    if steps.is_empty() {
+        return Err(String::from("Move has no steps"));
+    }
+    let mut swaps_used = 0;
+    for (position, step) in steps.iter().enumerate() {
+        let index = step.index();
+        let Some(tile) = board.tiles.get(index as usize) else {
+            return Err(format!(
+                "Step {position}: tile {index} is outside of the board"
+            ));
+        };
+        let name = format!("{} (tile {index})", board.tile_name(index));
+        if tile.frozen {
+            return Err(format!("Step {position}: {name} is frozen"));
         }
-        counter += 1;
-        existing_words.push(word_str);
-        final_words.push(word);
+        if steps[..position].iter().any(|x| x.index() == index) {
+            return Err(format!("Step {position}: {name} is used twice"));
+        }
+        if position > 0 {
+            let previous = steps[position - 1].index();
+            if !board.neighbors[previous as usize].contains(&index) {
+                return Err(format!(
+                    "Step {position}: {name} isn't adjacent to {}",
+                    board.tile_name(previous)
+                ));
+            }
+        }
+        match step {
+            Step::Normal { .. } if tile.letter == '?' => {
+                return Err(format!(
+                    "Step {position}: {name} is a wildcard, so it needs a letter"
+                ));
+            }
+            Step::Normal { .. } => (),
+            Step::Swap { .. } if tile.letter == '?' => {
+                return Err(format!(
+                    "Step {position}: {name} is a wildcard, so it can't be swapped"
+                ));
+            }
+            Step::Swap { new_letter, .. } if *new_letter == tile.letter => {
+                return Err(format!(
+                    "Step {position}: {name} is swapped to the letter it already has"
+                ));
+            }
+            Step::Swap { .. } => swaps_used += 1,
+            Step::Wildcard { .. } if tile.letter != '?' => {
+                return Err(format!("Step {position}: {name} isn't a wildcard"));
+            }
+            Step::Wildcard { .. } => (),
+        }
+    }
+    if swaps_used > swaps as usize {
+        return Err(format!(
+            "Move uses {swaps_used} swaps, but only {swaps} are available"
+        ));
+    }
+    // Checked before creating the word, because words that are too long to be in dictionary could overflow its counters.
+    let word: String = steps.iter().map(|x| x.letter(board)).collect();
+    if !contains_word(dictionary, &word) {
+        return Err(format!("{word} isn't in dictionary"));
     }
-    return (final_words, board);
+    Ok(Word::new(steps, board))
 }
@@ -4,15 +4,35 @@
 /// You can learn more in [crate::spellcast::SortedWordVec].
 pub const MAX_SOLUTIONS: usize = 256;
 
+/// Maximum number of swaps that is solved with plain exhaustive search.
+/// Anything higher uses deep swap search, which prunes branches that can't beat words already found.
+/// You can learn more in [crate::spellcast::solver_wrapper].
+pub const MAX_FAST_SWAPS: u8 = 3;
+
+/// Default maximum number of swaps servers accept in a request, higher swap counts get an error instead.
+/// Deep swap search gets slower with every swap, so requests with many swaps could keep solver threads busy for minutes.
+pub const MAX_SERVER_SWAPS: u8 = 8;
+
+/// Default time limit (in seconds) of a single server request, best words found by then are returned.
+pub const SERVER_TIME_LIMIT: f64 = 10.;
+
+/// Maximum number of connections servers handle at once.
+/// Each connection gets its own thread, so connections over the limit are refused instead.
+pub const MAX_CONNECTIONS: usize = 64;
+
+/// Maximum size of single server request in bytes (JSON line, HTTP body or WebSocket message).
+pub const MAX_REQUEST_BYTES: usize = 1 << 20;
+
 pub const GREEN: &str = "\x1B[32m";
 pub const GREY: &str = "\x1B[90m";
 pub const RED: &str = "\x1B[31m";
 pub const RESET: &str = "\x1B[0m";
+pub const YELLOW: &str = "\x1B[33m";
 
 #[macro_export]
 macro_rules! quit {
     ($($arg:tt)*) => {{
-        eprintln!("{}{}{}", crate::utils::RED, format!($($arg)*), crate::utils::RESET);
+        eprintln!("{}{}{}", $crate::utils::RED, format!($($arg)*), $crate::utils::RESET);
         std::process::exit(1);
     }};
 }
@@ -0,0 +1,70 @@
+//! JavaScript API for wasm32 builds, built with `--features wasm` (see README.md).
+//! Requests and results are the same JSON as in `serve` mode (see CLI.md), passed as strings so the API stays small.
+//! Solving is single-threaded and elapsed times in results are always 0 (there is no clock in wasm32-unknown-unknown), measure them in JavaScript instead.
+
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    api::SolveRequest,
+    dictionary::{count_words, load_dictionary_bytes, Node},
+    output::{JsonOutput, Timings},
+    spellcast::{solver_wrapper, Board, SolveControl},
+};
+
+/// Solver with loaded dictionary, it can be reused for any number of boards.
+#[wasm_bindgen]
+pub struct Solver {
+    dictionary: Vec<(char, Node)>,
+}
+
+#[wasm_bindgen]
+impl Solver {
+    /// Loads dictionary from bytes of dictionary file or its binary cache (e.g. fetched `dictionary.txt`).
+    #[wasm_bindgen(constructor)]
+    pub fn new(dictionary: &[u8]) -> Result<Solver, JsError> {
+        Ok(Solver {
+            dictionary: load_dictionary_bytes(dictionary).map_err(|e| JsError::new(&e))?,
+        })
+    }
+
+    /// Number of words in the dictionary.
+    #[wasm_bindgen(getter)]
+    pub fn words(&self) -> usize {
+        count_words(&self.dictionary)
+    }
+
+    /// Solves board from JSON solve request and returns JSON results (same as `-f json` output).
+    /// Invalid requests throw an error.
+    pub fn solve(&self, request: &str) -> Result<String, JsError> {
+        let request: SolveRequest = serde_json::from_str(request)
+            .map_err(|e| JsError::new(&format!("Invalid request: {e}")))?;
+        let (board, swaps, options) = request.prepare().map_err(|e| JsError::new(&e))?;
+        let words = solver_wrapper(
+            &board,
+            swaps,
+            1,
+            &self.dictionary,
+            request.count,
+            &options,
+            SolveControl::default(),
+        );
+        let timings = Timings {
+            dict: 0.,
+            solver: 0.,
+        };
+        serde_json::to_string(&JsonOutput::new(&board, &words, &timings, &options))
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+}
+
+/// Parses board string (strictly with `strict`) and returns it in canonical form, invalid boards throw an error.
+#[wasm_bindgen(js_name = normalizeBoard)]
+pub fn normalize_board(board: &str, strict: bool) -> Result<String, JsError> {
+    let board = if strict {
+        Board::parse_strict(board)
+    } else {
+        board.parse()
+    }
+    .map_err(|e| JsError::new(&e))?;
+    Ok(board.to_string())
+}
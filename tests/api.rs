@@ -0,0 +1,216 @@
+//! Tests of request handling shared by server modes ([Solver::handle]): responses, errors and request ids.
+
+use std::{
+    sync::{atomic::AtomicBool, Mutex},
+    time::Duration,
+};
+
+use common::{solver, SMALL_DICTIONARY};
+use serde_json::{json, Value};
+use spellcast_solver::{
+    api::Solver,
+    utils::{MAX_SERVER_SWAPS, SERVER_TIME_LIMIT},
+};
+
+mod common;
+
+/// 3x3 board with a DL tile:
+/// ```text
+/// c a+ t
+/// s o  g
+/// r e  d
+/// ```
+const BOARD: &str = "ca+tsogred";
+
+fn parse(response: &str) -> Value {
+    serde_json::from_str(response).expect("response should be valid JSON")
+}
+
+fn words(response: &Value) -> Vec<(&str, u64)> {
+    response["words"]
+        .as_array()
+        .expect("response should have words")
+        .iter()
+        .map(|x| (x["word"].as_str().unwrap(), x["score"].as_u64().unwrap()))
+        .collect()
+}
+
+#[test]
+fn request_id_is_echoed() {
+    let solver = solver(SMALL_DICTIONARY, 1);
+    for id in [json!(7), json!("move-3"), json!({"game": 1, "move": [2]})] {
+        let request = json!({"id": id, "board": BOARD, "count": 2});
+        let response = parse(&solver.handle(&request.to_string()).unwrap());
+        assert_eq!(response["id"], id);
+        assert_eq!(words(&response).len(), 2);
+        assert!(response.get("partial").is_none());
+        assert_eq!(response["elapsed_ms"]["dict"], 1.5);
+    }
+    // Without id, there is no id in the response either.
+    let response = parse(&solver.handle(&json!({"board": BOARD}).to_string()).unwrap());
+    assert!(response.get("id").is_none());
+}
+
+#[test]
+fn request_options_are_applied() {
+    let solver = solver(SMALL_DICTIONARY, 1);
+    let request = json!({"board": BOARD, "start": "A3", "pattern": "*d", "count": 5});
+    let response = parse(&solver.handle(&request.to_string()).unwrap());
+    let mut found: Vec<_> = words(&response).into_iter().map(|x| x.0).collect();
+    found.sort();
+    assert_eq!(found, ["red", "rod"]);
+    // Swap count of JSON board is used unless request overrides it.
+    let tiles: Vec<_> = "catsogrex"
+        .chars()
+        .map(|x| json!({ "letter": x }))
+        .collect();
+    let board = json!({"tiles": tiles, "swaps": 1});
+    let response = parse(
+        &solver
+            .handle(&json!({"board": board, "pattern": "red"}).to_string())
+            .unwrap(),
+    );
+    assert_eq!(words(&response).len(), 1);
+    assert_eq!(response["words"][0]["word"], "red");
+    assert_eq!(response["words"][0]["swaps_used"], 1);
+    let response = parse(
+        &solver
+            .handle(&json!({"board": board, "pattern": "red", "swaps": 0}).to_string())
+            .unwrap(),
+    );
+    assert!(words(&response).is_empty());
+}
+
+#[test]
+fn errors_are_responses_with_id() {
+    let solver = solver(SMALL_DICTIONARY, 1);
+    let cases = [
+        // Invalid JSON has no id to echo.
+        ("{", Value::Null, "Invalid request"),
+        // Id is echoed even if the rest of the request is invalid.
+        (
+            r#"{"id": 1, "board": "abc", "unknown": 0}"#,
+            json!(1),
+            "Invalid request",
+        ),
+        (r#"{"id": 2}"#, json!(2), "Invalid request"),
+        (r#"{"id": 3, "board": "abc"}"#, json!(3), "square"),
+        (
+            r#"{"id": 4, "board": "ca%tsogred", "strict": true}"#,
+            json!(4),
+            "unknown character",
+        ),
+        (
+            r#"{"id": 5, "board": "catsogred", "start": "Z9"}"#,
+            json!(5),
+            "Z9",
+        ),
+        (
+            r#"{"id": 6, "board": "catsogred", "regex": "("}"#,
+            json!(6),
+            "regex",
+        ),
+        (
+            r#"{"id": 7, "board": "catsogred", "adjacency": "3way"}"#,
+            json!(7),
+            "Expected 8way",
+        ),
+    ];
+    for (request, id, error) in cases {
+        let response = parse(&solver.handle(request).expect_err(request));
+        assert_eq!(response["id"], id, "{request}");
+        let message = response["error"].as_str().expect("error should be string");
+        assert!(
+            message.to_lowercase().contains(&error.to_lowercase()),
+            "{request}: {message}"
+        );
+        assert!(response.get("words").is_none());
+    }
+}
+
+#[test]
+fn live_requests_report_partial_responses() {
+    let solver = solver(SMALL_DICTIONARY, 1);
+    let partials = Mutex::new(vec![]);
+    let partial = |response: String| partials.lock().unwrap().push(parse(&response));
+    let request = json!({"id": "live", "board": BOARD, "swaps": 1}).to_string();
+    let response = parse(&solver.handle_live(&request, None, Some(&partial)).unwrap());
+    let partials = partials.into_inner().unwrap();
+    let last = partials.last().expect("partial responses should be sent");
+    for partial in &partials {
+        assert_eq!(partial["id"], "live");
+        assert_eq!(partial["partial"], true);
+    }
+    assert!(response.get("partial").is_none());
+    let sorted = |response: &Value| {
+        let mut words: Vec<_> = words(response)
+            .into_iter()
+            .map(|(w, s)| (w.to_owned(), s))
+            .collect();
+        words.sort();
+        words
+    };
+    assert_eq!(sorted(last), sorted(&response));
+}
+
+#[test]
+fn cancelled_request_still_responds() {
+    let solver = solver(SMALL_DICTIONARY, 1);
+    let cancel = AtomicBool::new(true);
+    let request = json!({"id": 1, "board": BOARD}).to_string();
+    let response = parse(&solver.handle_live(&request, Some(&cancel), None).unwrap());
+    assert_eq!(response["id"], 1);
+    assert!(words(&response).is_empty());
+}
+
+#[test]
+fn requests_are_limited() {
+    let mut solver = solver(SMALL_DICTIONARY, 1);
+    assert_eq!(solver.max_swaps, MAX_SERVER_SWAPS);
+    assert_eq!(
+        solver.time_limit,
+        Some(Duration::from_secs_f64(SERVER_TIME_LIMIT))
+    );
+    solver.max_swaps = 2;
+    let request = json!({"id": 1, "board": BOARD, "swaps": 3}).to_string();
+    let response = parse(
+        &solver
+            .handle(&request)
+            .expect_err("3 swaps should be rejected"),
+    );
+    assert_eq!(response["id"], 1);
+    assert!(response["error"].as_str().unwrap().contains("swaps"));
+    // Response of request that runs out of time says so.
+    let request = json!({"board": BOARD, "swaps": 2}).to_string();
+    let response = parse(&solver.handle(&request).unwrap());
+    assert!(response.get("timed_out").is_none());
+    solver.time_limit = Some(Duration::ZERO);
+    let response = parse(&solver.handle(&request).unwrap());
+    assert_eq!(response["timed_out"], true);
+}
+
+#[test]
+fn thread_pool_is_reused() {
+    let request = json!({"board": BOARD, "swaps": 2, "count": 5}).to_string();
+    let solve = |solver: &Solver| {
+        words(&parse(&solver.handle(&request).unwrap()))
+            .into_iter()
+            .map(|(w, s)| (w.to_owned(), s))
+            .collect::<Vec<_>>()
+    };
+    let pooled = solver(SMALL_DICTIONARY, 4);
+    let expected = solve(&pooled);
+    // Words with the same score can be in different order with different thread count.
+    let mut sorted = expected.clone();
+    let mut single = solve(&solver(SMALL_DICTIONARY, 1));
+    sorted.sort();
+    single.sort();
+    assert_eq!(sorted, single);
+    // Concurrent requests share the same threads.
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..4).map(|_| scope.spawn(|| solve(&pooled))).collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), expected);
+        }
+    });
+}
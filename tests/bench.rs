@@ -0,0 +1,45 @@
+//! Tests of the `bench` subcommand, run through the CLI binary.
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::{
+    path::Path,
+    process::{Command, Output},
+};
+
+use common::{DictionaryFile, SMALL_DICTIONARY};
+use serde_json::Value;
+
+mod common;
+
+fn bench(dictionary: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_spellcast-solver"))
+        .arg("-d")
+        .arg(dictionary)
+        .arg("bench")
+        .args(args)
+        .output()
+        .expect("CLI should start")
+}
+
+#[test]
+fn board_count_has_to_be_positive() {
+    let file = DictionaryFile::new("bench", SMALL_DICTIONARY);
+    let dictionary = file.path();
+    for count in ["0", "-1", "many"] {
+        let output = bench(dictionary, &["--boards", count]);
+        assert!(!output.status.success(), "--boards {count} should fail");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("positive number of boards"), "{stderr}");
+    }
+    // Stats of a single board are still numbers (not null) in JSON.
+    let output = bench(
+        dictionary,
+        &["--boards", "1", "--swaps", "0", "--json", "-"],
+    );
+    assert!(output.status.success());
+    let report: Value = serde_json::from_slice(&output.stdout).expect("report should be JSON");
+    let stats = &report["results"][0]["solver_ms"];
+    for stat in ["min", "avg", "max", "mdev"] {
+        assert!(stats[stat].is_f64(), "{stat} should be number: {report}");
+    }
+}
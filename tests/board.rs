@@ -0,0 +1,54 @@
+//! Tests of board parsing and formatting.
+
+use spellcast_solver::spellcast::Board;
+
+#[test]
+fn json_board_size_doesnt_overflow() {
+    let json = format!(
+        r#"{{"tiles":[{{"letter":"a"}}],"width":{},"height":{}}}"#,
+        usize::MAX,
+        usize::MAX
+    );
+    let error = Board::from_json(&json).expect_err("board should be invalid");
+    assert!(error.contains("to have"), "{error}");
+}
+
+/// Returns width, height and canonical board string of board.
+fn shape(board: &Board) -> (usize, usize, String) {
+    (board.width(), board.height(), board.to_string())
+}
+
+#[test]
+fn board_string_round_trips() {
+    for (input, width, height) in [
+        ("c$at!+/", 3, 1),
+        ("c/a*/t#", 1, 3),
+        ("a?/", 2, 1),
+        ("a\n?", 1, 2),
+        ("q", 1, 1),
+        ("abcd/efgh", 4, 2),
+        ("ab/cd/ef/gh", 2, 4),
+        ("abc$3def*ghi", 3, 3),
+    ] {
+        let board: Board = input.parse().unwrap_or_else(|e| panic!("{input:?}: {e}"));
+        assert_eq!(
+            (board.width(), board.height()),
+            (width, height),
+            "{input:?}"
+        );
+        let string = board.to_string();
+        let parsed: Board = string.parse().expect("canonical string should be valid");
+        assert_eq!(shape(&parsed), shape(&board), "{input:?} -> {string}");
+        let strict = Board::parse_strict(&string).expect("canonical string should be valid");
+        assert_eq!(shape(&strict), shape(&board), "{input:?} -> {string}");
+    }
+}
+
+#[test]
+fn single_row_is_square_without_slash() {
+    let board: Board = "abcdefghijklmnopqrstuvwxy\n".parse().unwrap();
+    assert_eq!((board.width(), board.height()), (5, 5));
+    let board: Board = "abcdefghijklmnopqrstuvwxy/".parse().unwrap();
+    assert_eq!((board.width(), board.height()), (25, 1));
+    assert!("cat".parse::<Board>().unwrap_err().contains("square"));
+}
@@ -0,0 +1,58 @@
+//! Code shared by integration tests.
+// Every test crate compiles all of it, but uses only part.
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+
+use spellcast_solver::{
+    api::{DictionaryInfo, Solver},
+    dictionary::{count_words, load_dictionary_tree},
+};
+
+pub mod reference;
+
+/// Small dictionary for tests that don't need the whole dictionary.txt.
+pub const SMALL_DICTIONARY: &str = "cat\ncats\ncoat\ncod\ncore\ndog\nred\nrod\ntoe";
+
+/// Dictionary file in temporary directory, removed together with its binary cache when dropped.
+pub struct DictionaryFile(PathBuf);
+
+impl DictionaryFile {
+    /// Writes `words` into new dictionary file.
+    /// `name` has to be unique within test crate, since loading dictionary also writes binary cache next to it.
+    pub fn new(name: &str, words: &str) -> DictionaryFile {
+        let path =
+            std::env::temp_dir().join(format!("spellcast-{}-{name}.txt", std::process::id()));
+        std::fs::write(&path, words).expect("dictionary file should be written");
+        let file = DictionaryFile(path);
+        file.remove_cache();
+        file
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    fn remove_cache(&self) {
+        let _ = std::fs::remove_file(self.0.with_extension("txt.bin"));
+    }
+}
+
+impl Drop for DictionaryFile {
+    fn drop(&mut self) {
+        self.remove_cache();
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Creates [Solver] with `threads` threads for dictionary of `words` (leaked, same as in server modes).
+/// Dictionary pretends to have taken 1.5ms to load, so its time can be told apart in responses.
+pub fn solver(words: &str, threads: u8) -> Solver {
+    let dictionary = Box::leak(Box::new(load_dictionary_tree(String::from(words))));
+    let info = DictionaryInfo {
+        path: String::from("test"),
+        words: count_words(dictionary),
+        load_ms: 1.5,
+    };
+    Solver::new(dictionary, info, threads).expect("solver should start")
+}
@@ -0,0 +1,282 @@
+//! Deliberately simple reference solver, used to test [solver_wrapper](spellcast_solver::spellcast::solver_wrapper) (see tests/reference.rs).
+//! It checks every dictionary word separately by trying every path on the board that could spell it, so it is very slow, but easy to verify by reading.
+//! Rules (adjacency, swaps, wildcards, frozen tiles and [SolveOptions]) are implemented from scratch instead of reusing solver code, so bugs in solver can't hide in shared code.
+//! Even letters and frozen tiles are read from canonical board string, since they aren't a part of public API.
+//! Only scoring is shared (words are scored with [Word::new]), since it doesn't depend on how the word was found.
+
+use spellcast_solver::{
+    dictionary::Node,
+    spellcast::{Adjacency, Board, SolveOptions, Step, Word},
+};
+
+/// Board with rules that words found on it have to follow.
+pub struct Reference<'a> {
+    board: &'a Board,
+    /// Letter of each tile and whether it is frozen.
+    tiles: Vec<(char, bool)>,
+    /// Same rule that was passed to [Board::set_adjacency] (board doesn't store the rule itself).
+    adjacency: &'a Adjacency,
+    swaps: u8,
+    options: &'a SolveOptions,
+    /// Tiles that can be reached from each tile, found with [Reference::is_adjacent] once so search doesn't have to check every tile.
+    next: Vec<Vec<u16>>,
+}
+
+impl<'a> Reference<'a> {
+    pub fn new(
+        board: &'a Board,
+        adjacency: &'a Adjacency,
+        swaps: u8,
+        options: &'a SolveOptions,
+    ) -> Reference<'a> {
+        let mut reference = Reference {
+            board,
+            tiles: tiles(board),
+            adjacency,
+            swaps,
+            options,
+            next: vec![],
+        };
+        let tiles = 0..reference.tiles.len() as u16;
+        reference.next = tiles
+            .clone()
+            .map(|from| {
+                tiles
+                    .clone()
+                    .filter(|to| reference.is_adjacent(from, *to))
+                    .collect()
+            })
+            .collect();
+        reference
+    }
+
+    /// Returns whether step can go from tile @ `from` to tile @ `to`.
+    fn is_adjacent(&self, from: u16, to: u16) -> bool {
+        if from == to {
+            return false;
+        }
+        match self.adjacency {
+            Adjacency::Grid {
+                diagonals,
+                wraparound,
+            } => {
+                let (width, height) = (self.board.width() as isize, self.board.height() as isize);
+                let (x, y) = (from as isize % width, from as isize / width);
+                for dx in [-1, 0, 1] {
+                    for dy in [-1, 0, 1] {
+                        if (dx, dy) == (0, 0) || (dx != 0 && dy != 0 && !diagonals) {
+                            continue;
+                        }
+                        let (mut nx, mut ny) = (x + dx, y + dy);
+                        if *wraparound {
+                            nx = nx.rem_euclid(width);
+                            ny = ny.rem_euclid(height);
+                        }
+                        if (0..width).contains(&nx)
+                            && (0..height).contains(&ny)
+                            && ny * width + nx == to as isize
+                        {
+                            return true;
+                        }
+                    }
+                }
+                false
+            }
+            Adjacency::Custom(list) => list[from as usize].contains(&to),
+        }
+    }
+
+    /// Returns whether tile @ `index` can be used at all.
+    fn is_usable(&self, index: u16) -> bool {
+        !self.tiles[index as usize].1 && !self.options.exclude.contains(&index)
+    }
+
+    /// Returns step that uses tile @ `index` as `letter`, which is a swap if tile has another letter (swap limit isn't checked here).
+    fn step(&self, index: u16, letter: char) -> Step {
+        match self.tiles[index as usize].0 {
+            tile_letter if tile_letter == letter => Step::Normal { index },
+            '?' => Step::Wildcard { index, letter },
+            _ => Step::Swap {
+                index,
+                new_letter: letter,
+            },
+        }
+    }
+
+    /// Checks that `steps` spell `word` following all rules, returns reason if they don't.
+    /// It doesn't check whether `word` is in dictionary.
+    pub fn check(&self, steps: &[Step], word: &str) -> Result<(), String> {
+        let letters: Vec<char> = word.chars().collect();
+        if steps.is_empty() || steps.len() != letters.len() {
+            return Err(format!(
+                "{} steps for {} letters",
+                steps.len(),
+                letters.len()
+            ));
+        }
+        let mut swaps = 0;
+        for (position, (step, letter)) in steps.iter().zip(&letters).enumerate() {
+            let index = step.index();
+            if index as usize >= self.tiles.len() {
+                return Err(format!("Tile {index} is outside of the board"));
+            }
+            if !self.is_usable(index) {
+                return Err(format!("Tile {index} can't be used"));
+            }
+            if steps[..position].iter().any(|x| x.index() == index) {
+                return Err(format!("Tile {index} is used twice"));
+            }
+            if position > 0 && !self.is_adjacent(steps[position - 1].index(), index) {
+                return Err(format!("Tile {index} isn't adjacent to previous tile"));
+            }
+            let expected = self.step(index, *letter);
+            let matches = match (step, &expected) {
+                (Step::Normal { .. }, Step::Normal { .. }) => true,
+                (Step::Swap { new_letter, .. }, Step::Swap { .. }) => new_letter == letter,
+                (Step::Wildcard { letter: used, .. }, Step::Wildcard { .. }) => used == letter,
+                _ => false,
+            };
+            if !matches {
+                return Err(format!("Step {step:?} doesn't make letter {letter}"));
+            }
+            if matches!(step, Step::Swap { .. }) {
+                swaps += 1;
+            }
+        }
+        if swaps > self.swaps {
+            return Err(format!(
+                "{swaps} swaps used, but only {} allowed",
+                self.swaps
+            ));
+        }
+        let options = self.options;
+        let (first, last) = (steps[0].index(), steps[steps.len() - 1].index());
+        if !options.start.is_empty() && !options.start.contains(&first) {
+            return Err(format!("Word can't start on tile {first}"));
+        }
+        if !options.end.is_empty() && !options.end.contains(&last) {
+            return Err(format!("Word can't end on tile {last}"));
+        }
+        if let Some(index) = options
+            .include
+            .iter()
+            .find(|x| !steps.iter().any(|step| step.index() == **x))
+        {
+            return Err(format!("Word doesn't use tile {index}"));
+        }
+        if steps.len() < options.min_length as usize
+            || options.max_length.is_some_and(|x| steps.len() > x as usize)
+        {
+            return Err(format!("Word length {} isn't allowed", steps.len()));
+        }
+        if !options.patterns.iter().all(|x| x.is_match(word)) {
+            return Err(String::from("Word doesn't match patterns"));
+        }
+        if options
+            .word_list
+            .as_ref()
+            .is_some_and(|x| !x.contains(word))
+        {
+            return Err(String::from("Word isn't in word list"));
+        }
+        Ok(())
+    }
+
+    /// Returns the best way (highest sorting score) to form `word`, if it can be formed at all.
+    /// It tries every path of distinct usable adjacent tiles that could spell it with available swaps.
+    pub fn best(&self, word: &str) -> Option<Word> {
+        let letters: Vec<char> = word.chars().collect();
+        let mut best: Option<Word> = None;
+        let mut steps = vec![];
+        for index in 0..self.tiles.len() as u16 {
+            self.extend(&letters, index, self.swaps, &mut steps, word, &mut best);
+        }
+        best
+    }
+
+    /// Tries to use tile @ `index` for the next letter, then every possible next tile.
+    fn extend(
+        &self,
+        letters: &[char],
+        index: u16,
+        swaps: u8,
+        steps: &mut Vec<Step>,
+        word: &str,
+        best: &mut Option<Word>,
+    ) {
+        if !self.is_usable(index) || steps.iter().any(|x| x.index() == index) {
+            return;
+        }
+        let step = self.step(index, letters[steps.len()]);
+        let swaps = match step {
+            Step::Swap { .. } if swaps == 0 => return,
+            Step::Swap { .. } => swaps - 1,
+            _ => swaps,
+        };
+        steps.push(step);
+        if steps.len() == letters.len() {
+            if self.check(steps, word).is_ok() {
+                let found = Word::new(steps.clone(), self.board);
+                if best
+                    .as_ref()
+                    .is_none_or(|x| found.sorting_score > x.sorting_score)
+                {
+                    *best = Some(found);
+                }
+            }
+        } else {
+            for next in &self.next[index as usize] {
+                self.extend(letters, *next, swaps, steps, word, best);
+            }
+        }
+        steps.pop();
+    }
+
+    /// Returns the best way to form every dictionary word that can be formed, sorted from best to worst (then alphabetically).
+    pub fn solve(&self, dictionary: &[(char, Node)]) -> Vec<Word> {
+        let mut words: Vec<(String, Word)> = dictionary_words(dictionary)
+            .into_iter()
+            .filter_map(|word| self.best(&word).map(|best| (word, best)))
+            .collect();
+        words.sort_by(|a, b| {
+            b.1.sorting_score
+                .cmp(&a.1.sorting_score)
+                .then(a.0.cmp(&b.0))
+        });
+        words.into_iter().map(|x| x.1).collect()
+    }
+}
+
+/// Returns letter of each tile and whether it is frozen, read from canonical board string.
+/// Each tile is a letter (or `?`) followed by its modifiers, where `#` marks frozen tile.
+fn tiles(board: &Board) -> Vec<(char, bool)> {
+    let mut tiles: Vec<(char, bool)> = vec![];
+    for char in board.to_string().chars() {
+        match char {
+            'a'..='z' | '?' => tiles.push((char, false)),
+            '#' => tiles.last_mut().expect("modifier should follow letter").1 = true,
+            _ => (),
+        }
+    }
+    assert_eq!(tiles.len(), board.width() * board.height());
+    tiles
+}
+
+/// Returns every word in dictionary tree.
+pub fn dictionary_words(dictionary: &[(char, Node)]) -> Vec<String> {
+    fn walk(next_letters: &[(char, Node)], prefix: &mut String, words: &mut Vec<String>) {
+        for (letter, node) in next_letters {
+            prefix.push(*letter);
+            if matches!(node, Node::Word | Node::Both { .. }) {
+                words.push(prefix.clone());
+            }
+            if let Node::Prefix { next_letters } | Node::Both { next_letters } = node {
+                walk(next_letters, prefix, words);
+            }
+            prefix.pop();
+        }
+    }
+    let mut words = vec![];
+    walk(dictionary, &mut String::new(), &mut words);
+    words
+}
@@ -0,0 +1,211 @@
+//! Tests of runtime control over the search ([SolveControl]): cancellation, deadlines and progress reports.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use rayon_core::{ThreadPool, ThreadPoolBuilder};
+use spellcast_solver::{
+    dictionary::{load_dictionary_tree, Node},
+    spellcast::{solver_wrapper, Board, SolveControl, SolveOptions, Word},
+};
+
+const BOARD: &str = "dwtgmlqucavltalsfyxaaoyjf";
+const MOVE_COUNT: u8 = 5;
+
+fn dictionary() -> Vec<(char, Node)> {
+    load_dictionary_tree(
+        std::fs::read_to_string("dictionary.txt").expect("dictionary.txt should exist"),
+    )
+}
+
+fn board() -> Board {
+    BOARD.parse().expect("board should be valid")
+}
+
+fn pool() -> ThreadPool {
+    ThreadPoolBuilder::new().num_threads(4).build().unwrap()
+}
+
+/// Solves `board` with `swaps` (on `pool` if there is one), sets cancel flag after `delay` and returns how long the search took.
+/// Word has to start on the first tile, so there are only a few initial calls and cancellation has to stop them in the middle.
+fn cancelled_after(
+    dictionary: &Vec<(char, Node)>,
+    board: &Board,
+    swaps: u8,
+    delay: Duration,
+    pool: Option<&ThreadPool>,
+) -> Duration {
+    let options = SolveOptions {
+        start: vec![0],
+        ..SolveOptions::default()
+    };
+    let cancel = AtomicBool::new(false);
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            std::thread::sleep(delay);
+            cancel.store(true, Ordering::Relaxed);
+        });
+        let clock = Instant::now();
+        solver_wrapper(
+            board,
+            swaps,
+            pool.map_or(1, |x| x.current_num_threads() as u8),
+            dictionary,
+            MOVE_COUNT,
+            &options,
+            SolveControl {
+                cancel: Some(&cancel),
+                progress: None,
+                pool,
+                deadline: None,
+            },
+        );
+        clock.elapsed()
+    })
+}
+
+#[test]
+fn cancelled_search_stops() {
+    let dictionary = dictionary();
+    // With that many wildcards, every initial call takes minutes if it isn't cancelled.
+    let board: Board = format!("s{}", "?".repeat(24)).parse().unwrap();
+    let pool = pool();
+    for swaps in [0, 4] {
+        for pool in [None, Some(&pool)] {
+            let elapsed =
+                cancelled_after(&dictionary, &board, swaps, Duration::from_millis(200), pool);
+            assert!(
+                elapsed < Duration::from_secs(3),
+                "search with {swaps} swaps took {elapsed:?} after being cancelled"
+            );
+        }
+    }
+}
+
+#[test]
+fn deadline_stops_deep_swap_search() {
+    let board = board();
+    let dictionary = dictionary();
+    let pool = pool();
+    // Full search with this many swaps doesn't finish in minutes.
+    for pool in [None, Some(&pool)] {
+        let clock = Instant::now();
+        let words = solver_wrapper(
+            &board,
+            20,
+            pool.map_or(1, |x| x.current_num_threads() as u8),
+            &dictionary,
+            MOVE_COUNT,
+            &SolveOptions::default(),
+            SolveControl {
+                pool,
+                deadline: Some(clock + Duration::from_millis(500)),
+                ..SolveControl::default()
+            },
+        );
+        let elapsed = clock.elapsed();
+        assert!(
+            elapsed < Duration::from_secs(3),
+            "search took {elapsed:?} with 500ms deadline"
+        );
+        assert!(
+            !words.is_empty(),
+            "words found before deadline are returned"
+        );
+    }
+}
+
+#[test]
+fn search_cancelled_beforehand_finds_nothing() {
+    let cancel = AtomicBool::new(true);
+    let words = solver_wrapper(
+        &board(),
+        1,
+        2,
+        &dictionary(),
+        MOVE_COUNT,
+        &SolveOptions::default(),
+        SolveControl {
+            cancel: Some(&cancel),
+            progress: None,
+            pool: None,
+            deadline: None,
+        },
+    );
+    assert!(words.is_empty());
+}
+
+#[test]
+fn progress_ends_with_final_words() {
+    let board = board();
+    let dictionary = dictionary();
+    let reports: Mutex<Vec<Vec<(String, u16)>>> = Mutex::new(vec![]);
+    let progress = |words: &[Word]| {
+        let words = words
+            .iter()
+            .map(|x| (x.word(&board, false), x.score))
+            .collect();
+        reports.lock().unwrap().push(words);
+    };
+    let words = solver_wrapper(
+        &board,
+        1,
+        1,
+        &dictionary,
+        MOVE_COUNT,
+        &SolveOptions::default(),
+        SolveControl {
+            cancel: None,
+            progress: Some(&progress),
+            pool: None,
+            deadline: None,
+        },
+    );
+    let reports = reports.into_inner().unwrap();
+    let mut words: Vec<_> = words
+        .iter()
+        .map(|x| (x.word(&board, false), x.score))
+        .collect();
+    let mut last = reports.last().expect("progress should be reported").clone();
+    // Words with the same score can be in different order.
+    words.sort();
+    last.sort();
+    assert_eq!(last, words);
+    // Words are only reported when they change.
+    assert!(reports.windows(2).all(|x| x[0] != x[1]));
+}
+
+#[test]
+fn pool_finds_same_words_as_own_threads() {
+    let board = board();
+    let dictionary = dictionary();
+    let pool = pool();
+    let solve = |pool| {
+        solver_wrapper(
+            &board,
+            2,
+            4,
+            &dictionary,
+            MOVE_COUNT,
+            &SolveOptions::default(),
+            SolveControl {
+                pool,
+                ..SolveControl::default()
+            },
+        )
+        .iter()
+        .map(|x| (x.word(&board, false), x.score, format!("{:?}", x.steps)))
+        .collect::<Vec<_>>()
+    };
+    let words = solve(None);
+    assert_eq!(words.len(), MOVE_COUNT as usize);
+    // Pool is reused, so solve on it more than once.
+    for _ in 0..3 {
+        assert_eq!(solve(Some(&pool)), words);
+    }
+}
@@ -0,0 +1,102 @@
+//! Tests of dictionary loading, including long words that only fit on bigger boards.
+
+use spellcast_solver::{
+    dictionary::{count_words, load_dictionary_tree, Node},
+    spellcast::{solver_wrapper, Adjacency, Board, SolveControl, SolveOptions},
+};
+
+/// Returns every word in dictionary tree.
+fn words(next_letters: &[(char, Node)], prefix: &mut String, found: &mut Vec<String>) {
+    for (letter, node) in next_letters {
+        prefix.push(*letter);
+        if matches!(node, Node::Word | Node::Both { .. }) {
+            found.push(prefix.clone());
+        }
+        if let Node::Prefix { next_letters } | Node::Both { next_letters } = node {
+            words(next_letters, prefix, found);
+        }
+        prefix.pop();
+    }
+}
+
+fn load(content: &str) -> Vec<String> {
+    let dictionary = load_dictionary_tree(content.to_owned());
+    let mut found = vec![];
+    words(&dictionary, &mut String::new(), &mut found);
+    assert_eq!(count_words(&dictionary), found.len());
+    found.sort();
+    found
+}
+
+#[test]
+fn unsorted_dictionary_keeps_prefix_words() {
+    // "cat" is added after "cats", so its node already exists as a prefix.
+    assert_eq!(load("cats\ncat\ndog"), ["cat", "cats", "dog"]);
+    assert_eq!(load("acaa\naca\n"), ["aca", "acaa"]);
+}
+
+#[test]
+fn word_length_is_counted_in_characters() {
+    assert_eq!(load("café\nnaïve\néte"), ["café", "naïve", "éte"]);
+    // Words shorter than 3 characters are ignored, even when they take more bytes than that.
+    assert_eq!(load("éé\nab\ncat"), ["cat"]);
+    // There is no upper limit.
+    let long = "é".repeat(40);
+    assert_eq!(load(&format!("{long}\nab")), [long]);
+}
+
+/// Lays `word` out in rows of `width` letters, every other row reversed, so the word snakes through the whole board.
+fn snake_board(word: &str, width: usize) -> Board {
+    let letters: Vec<char> = word.chars().collect();
+    let rows: Vec<String> = letters
+        .chunks(width)
+        .enumerate()
+        .map(|(row, letters)| match row % 2 {
+            0 => letters.iter().collect(),
+            _ => letters.iter().rev().collect(),
+        })
+        .collect();
+    rows.join("/").parse().expect("board should be valid")
+}
+
+#[test]
+fn long_words_fit_bigger_boards() {
+    // High-scoring letters, so letter points of the long words add up to more than u8::MAX.
+    let word = "qzjxk".repeat(8)[..36].to_owned();
+    let dictionary = load_dictionary_tree(format!("{word}\n{}\nqzj", &word[..30]));
+    let mut board = snake_board(&word, 6);
+    // Deep swap search (4 swaps) bounds points of words with u16, with 4-way adjacency and fixed start it is fast enough.
+    board
+        .set_adjacency(&Adjacency::from_name("4way").unwrap())
+        .unwrap();
+    let options = SolveOptions {
+        start: vec![0],
+        ..SolveOptions::default()
+    };
+    for swaps in [0, 4] {
+        let words = solver_wrapper(
+            &board,
+            swaps,
+            1,
+            &dictionary,
+            3,
+            &options,
+            SolveControl::default(),
+        );
+        let found: Vec<String> = words.iter().map(|x| x.word(&board, false)).collect();
+        assert_eq!(found, [&word, &word[..30], "qzj"], "{swaps} swaps");
+    }
+    // 5x5 board only has room for the short word.
+    let board = snake_board(&word[..25], 5);
+    let words = solver_wrapper(
+        &board,
+        0,
+        1,
+        &dictionary,
+        3,
+        &SolveOptions::default(),
+        SolveControl::default(),
+    );
+    let found: Vec<String> = words.iter().map(|x| x.word(&board, false)).collect();
+    assert_eq!(found, ["qzj"]);
+}
@@ -0,0 +1,176 @@
+//! Tests of C ABI (src/ffi.rs), called from Rust the same way C code would call it.
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::{
+    ffi::{c_char, CStr, CString},
+    ptr,
+};
+
+use common::{DictionaryFile, SMALL_DICTIONARY};
+use spellcast_solver::ffi::*;
+
+mod common;
+
+/// 3x3 board:
+/// ```text
+/// c a$ t
+/// s o  g
+/// r e  d
+/// ```
+const BOARD: &str = "ca$tsogred";
+
+/// Returns error stored by the last failed call.
+fn last_error() -> String {
+    let error = spellcast_last_error();
+    assert!(!error.is_null(), "error should be stored");
+    unsafe { CStr::from_ptr(error) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn c_string(s: &str) -> CString {
+    CString::new(s).unwrap()
+}
+
+/// Loads small dictionary through the ABI, every test uses its own file (see [DictionaryFile::new]).
+fn load_dictionary(name: &str) -> *mut SpellcastDictionary {
+    let file = DictionaryFile::new(name, SMALL_DICTIONARY);
+    let dictionary =
+        unsafe { spellcast_dictionary_load(c_string(file.path().to_str().unwrap()).as_ptr()) };
+    assert!(!dictionary.is_null(), "{}", last_error());
+    dictionary
+}
+
+#[test]
+fn solves_board() {
+    let dictionary = load_dictionary("solve");
+    let board_string = c_string(BOARD);
+    unsafe {
+        let board = spellcast_board_parse(board_string.as_ptr());
+        assert!(!board.is_null(), "{}", last_error());
+        let mut options = spellcast_options_default();
+        options.swaps = 1;
+        options.move_count = 3;
+        let results = spellcast_solve(dictionary, board, &options);
+        assert!(!results.is_null(), "{}", last_error());
+        let count = spellcast_results_len(results);
+        assert_eq!(count, 3);
+        let mut words = vec![];
+        for index in 0..count {
+            let mut word = SpellcastWord {
+                word: ptr::null(),
+                score: 0,
+                gems_collected: 0,
+                swaps_used: 0,
+                tiles: ptr::null(),
+                tile_count: 0,
+            };
+            assert!(spellcast_results_get(results, index, &mut word));
+            let string = CStr::from_ptr(word.word).to_str().unwrap().to_owned();
+            let tiles = std::slice::from_raw_parts(word.tiles, word.tile_count);
+            assert_eq!(tiles.len(), string.len());
+            assert!(word.swaps_used <= 1);
+            words.push((string, word.score));
+        }
+        assert!(words.windows(2).all(|x| x[0].1 >= x[1].1), "{words:?}");
+        let json: serde_json::Value = serde_json::from_str(
+            CStr::from_ptr(spellcast_results_json(results))
+                .to_str()
+                .unwrap(),
+        )
+        .expect("results should be valid JSON");
+        assert_eq!(json["words"][0]["word"], words[0].0.as_str());
+        assert_eq!(json["words"][0]["score"], words[0].1);
+        // Index out of range and NULL word are rejected.
+        let mut word = std::mem::zeroed::<SpellcastWord>();
+        assert!(!spellcast_results_get(results, count, &mut word));
+        assert!(!spellcast_results_get(results, 0, ptr::null_mut()));
+        spellcast_results_free(results);
+        spellcast_board_free(board);
+        spellcast_dictionary_free(dictionary);
+    }
+}
+
+#[test]
+fn options_are_applied() {
+    let dictionary = load_dictionary("options");
+    let board_string = c_string(BOARD);
+    let pattern = c_string("*od");
+    let start = c_string("A3");
+    unsafe {
+        let board = spellcast_board_parse(board_string.as_ptr());
+        let mut options = spellcast_options_default();
+        options.pattern = pattern.as_ptr();
+        options.start = start.as_ptr();
+        let results = spellcast_solve(dictionary, board, &options);
+        assert!(!results.is_null(), "{}", last_error());
+        assert_eq!(spellcast_results_len(results), 1);
+        let mut word = std::mem::zeroed::<SpellcastWord>();
+        assert!(spellcast_results_get(results, 0, &mut word));
+        assert_eq!(CStr::from_ptr(word.word).to_str().unwrap(), "rod");
+        spellcast_results_free(results);
+        // Options that can't be parsed are reported as errors.
+        let bad_tile = c_string("Z9");
+        options.start = bad_tile.as_ptr();
+        assert!(spellcast_solve(dictionary, board, &options).is_null());
+        assert!(last_error().contains("Z9"), "{}", last_error());
+        spellcast_board_free(board);
+        spellcast_dictionary_free(dictionary);
+    }
+}
+
+#[test]
+fn null_pointers_are_rejected() {
+    unsafe {
+        assert!(spellcast_dictionary_load(ptr::null()).is_null());
+        assert!(last_error().contains("NULL"));
+        assert!(spellcast_board_parse(ptr::null()).is_null());
+        assert!(last_error().contains("NULL"));
+        assert!(spellcast_solve(ptr::null(), ptr::null(), ptr::null()).is_null());
+        assert!(last_error().contains("NULL"));
+        assert_eq!(spellcast_results_len(ptr::null()), 0);
+        assert!(spellcast_results_json(ptr::null()).is_null());
+        let mut word = std::mem::zeroed::<SpellcastWord>();
+        assert!(!spellcast_results_get(ptr::null(), 0, &mut word));
+        // Freeing NULL does nothing.
+        spellcast_dictionary_free(ptr::null_mut());
+        spellcast_board_free(ptr::null_mut());
+        spellcast_results_free(ptr::null_mut());
+    }
+}
+
+#[test]
+fn invalid_input_is_reported() {
+    let invalid_utf8: [u8; 3] = [0xff, 0xfe, 0];
+    let invalid_utf8 = invalid_utf8.as_ptr() as *const c_char;
+    unsafe {
+        assert!(spellcast_dictionary_load(invalid_utf8).is_null());
+        assert!(last_error().contains("UTF-8"), "{}", last_error());
+        assert!(spellcast_board_parse(invalid_utf8).is_null());
+        assert!(last_error().contains("UTF-8"), "{}", last_error());
+        let missing = c_string("/nonexistent/dictionary.txt");
+        assert!(spellcast_dictionary_load(missing.as_ptr()).is_null());
+        assert!(last_error().contains("Failed to load dictionary"));
+        let board = c_string("abc");
+        assert!(spellcast_board_parse(board.as_ptr()).is_null());
+        assert!(last_error().contains("Invalid board"), "{}", last_error());
+
+        let dictionary = load_dictionary("invalid");
+        let board_string = c_string(BOARD);
+        let board = spellcast_board_parse(board_string.as_ptr());
+        let mut options = spellcast_options_default();
+        options.regex = invalid_utf8;
+        assert!(spellcast_solve(dictionary, board, &options).is_null());
+        assert!(
+            last_error().contains("Regex is not valid UTF-8"),
+            "{}",
+            last_error()
+        );
+        let adjacency = c_string("3way");
+        options.regex = ptr::null();
+        options.adjacency = adjacency.as_ptr();
+        assert!(spellcast_solve(dictionary, board, &options).is_null());
+        spellcast_board_free(board);
+        spellcast_dictionary_free(dictionary);
+    }
+}
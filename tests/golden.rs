@@ -0,0 +1,128 @@
+//! Golden-file tests: boards from tests/golden/boards.txt are solved with full dictionary.txt and results are compared with tests/golden/<name>.json.
+//! When scoring rules (or output format) change intentionally, regenerate expected results with `UPDATE_GOLDEN=1 cargo test --test golden` and review the diff.
+
+use std::path::{Path, PathBuf};
+
+use spellcast_solver::{
+    dictionary::{load_dictionary_tree, Node},
+    output::{JsonOutput, Timings},
+    spellcast::{solver_wrapper, Board, SolveControl, SolveOptions},
+};
+
+const GOLDEN_DIR: &str = "tests/golden";
+/// Number of best words stored for each board.
+const MOVE_COUNT: u8 = 10;
+
+/// Single board of the corpus.
+struct Case {
+    name: String,
+    swaps: u8,
+    board: Board,
+}
+
+/// Parses corpus file, lines are `<name> <swaps> <board string>`, lines starting with `//` are comments.
+fn corpus() -> Vec<Case> {
+    let content = std::fs::read_to_string(Path::new(GOLDEN_DIR).join("boards.txt"))
+        .expect("corpus should be readable");
+    content
+        .lines()
+        .filter(|x| !x.trim().is_empty() && !x.starts_with("//"))
+        .map(|line| {
+            let mut parts = line.split_whitespace();
+            let (Some(name), Some(swaps), Some(board), None) =
+                (parts.next(), parts.next(), parts.next(), parts.next())
+            else {
+                panic!("Invalid corpus line {line:?}");
+            };
+            Case {
+                name: name.to_owned(),
+                swaps: swaps.parse().expect("swap count should be a number"),
+                board: board.parse().expect("board should be valid"),
+            }
+        })
+        .collect()
+}
+
+/// Solves the board single-threaded (so words with equal score always come in the same order) and returns pretty-printed JSON output.
+/// Elapsed times are always 0, so output only changes when results do.
+fn solve(dictionary: &Vec<(char, Node)>, case: &Case) -> String {
+    let options = SolveOptions::default();
+    let words = solver_wrapper(
+        &case.board,
+        case.swaps,
+        1,
+        dictionary,
+        MOVE_COUNT,
+        &options,
+        SolveControl::default(),
+    );
+    let timings = Timings {
+        dict: 0.,
+        solver: 0.,
+    };
+    let output = JsonOutput::new(&case.board, &words, &timings, &options);
+    serde_json::to_string_pretty(&output).expect("output should serialise") + "\n"
+}
+
+/// Returns description of the first difference between expected and actual output.
+fn first_difference(expected: &str, actual: &str) -> String {
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    for line in 1.. {
+        match (expected_lines.next(), actual_lines.next()) {
+            (Some(a), Some(b)) if a == b => continue,
+            (None, None) => break,
+            (a, b) => {
+                return format!(
+                    "line {line}: expected {}, got {}",
+                    a.map_or("end of file", str::trim),
+                    b.map_or("end of file", str::trim)
+                )
+            }
+        }
+    }
+    String::from("no difference")
+}
+
+#[test]
+fn golden_results() {
+    let content = std::fs::read_to_string("dictionary.txt").expect("dictionary.txt should exist");
+    let dictionary = load_dictionary_tree(content);
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let cases = corpus();
+    // Boards are independent, so they are solved in parallel to keep the test quick.
+    let outputs: Vec<String> = std::thread::scope(|scope| {
+        let threads: Vec<_> = cases
+            .iter()
+            .map(|case| scope.spawn(|| solve(&dictionary, case)))
+            .collect();
+        threads
+            .into_iter()
+            .map(|x| x.join().expect("solver shouldn't panic"))
+            .collect()
+    });
+    let mut failures = vec![];
+    for (case, actual) in cases.iter().zip(outputs) {
+        let path = PathBuf::from(GOLDEN_DIR).join(format!("{}.json", case.name));
+        if update {
+            std::fs::write(&path, actual).expect("golden file should be writable");
+            continue;
+        }
+        match std::fs::read_to_string(&path) {
+            Ok(expected) if expected == actual => (),
+            Ok(expected) => failures.push(format!(
+                "{} ({} swaps, {}): {}",
+                case.name,
+                case.swaps,
+                case.board,
+                first_difference(&expected, &actual)
+            )),
+            Err(e) => failures.push(format!("{}: failed to read {path:?}: {e}", case.name)),
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "Results differ from golden files:\n{}\nIf the change is intentional, run `UPDATE_GOLDEN=1 cargo test --test golden`",
+        failures.join("\n")
+    );
+}
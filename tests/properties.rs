@@ -0,0 +1,269 @@
+//! Property-based tests of board and dictionary parsers and invariants of solver results.
+//! Parsers have to handle any input without panicking, and every word returned by solver has to be a valid move on the board.
+//! Fuzz targets for the same parsers are in fuzz/ (see README.md).
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::{
+    collections::{BTreeSet, HashSet},
+    sync::OnceLock,
+};
+
+use common::reference::dictionary_words;
+use proptest::prelude::*;
+use spellcast_solver::{
+    dictionary::{load_dictionary_bytes, load_dictionary_tree, Node},
+    spellcast::{solver_wrapper, verify, Board, SolveControl, SolveOptions, Step, Word},
+};
+
+mod common;
+
+/// Dictionary tree and set of its words.
+type Dictionary = (Vec<(char, Node)>, HashSet<String>);
+
+/// Every 50th word of dictionary.txt and set of its words, loaded once for all cases.
+fn dictionary() -> &'static Dictionary {
+    static DICTIONARY: OnceLock<Dictionary> = OnceLock::new();
+    DICTIONARY.get_or_init(|| {
+        let content =
+            std::fs::read_to_string("dictionary.txt").expect("dictionary.txt should exist");
+        let words: Vec<&str> = content.lines().step_by(50).collect();
+        let dictionary = load_dictionary_tree(words.join("\n"));
+        let set = dictionary_words(&dictionary).into_iter().collect();
+        (dictionary, set)
+    })
+}
+
+/// Tile as it is generated, so tests know what the board looks like without relying on parser.
+#[derive(Debug, Clone)]
+struct TileSpec {
+    letter: char,
+    word_multiplier: u8,
+    letter_multiplier: u8,
+    gem: bool,
+    frozen: bool,
+}
+
+impl TileSpec {
+    /// Returns tile in board string syntax.
+    fn to_board_string(&self) -> String {
+        let mut buf = self.letter.to_string();
+        match self.word_multiplier {
+            1 => (),
+            2 => buf.push('$'),
+            multiplier => buf += &format!("${multiplier}"),
+        }
+        match self.letter_multiplier {
+            2 => buf.push('+'),
+            3 => buf.push('*'),
+            _ => (),
+        }
+        if self.gem {
+            buf.push('!');
+        }
+        if self.frozen {
+            buf.push('#');
+        }
+        buf
+    }
+}
+
+fn tile() -> impl Strategy<Value = TileSpec> {
+    (
+        prop_oneof![20 => proptest::char::range('a', 'z'), 1 => Just('?')],
+        prop_oneof![8 => Just(1u8), 1 => Just(2), 1 => 3..=9u8],
+        prop_oneof![8 => Just(1u8), 1 => Just(2), 1 => Just(3)],
+        proptest::bool::weighted(0.2),
+        proptest::bool::weighted(0.1),
+    )
+        .prop_map(
+            |(letter, word_multiplier, letter_multiplier, gem, frozen)| TileSpec {
+                letter,
+                word_multiplier,
+                letter_multiplier,
+                gem,
+                frozen,
+            },
+        )
+}
+
+/// Generates board of up to 5x5 tiles, returns its width and tiles (row by row).
+/// Single-row boards are included too, [board_string] makes sure they aren't read as square boards.
+fn board() -> impl Strategy<Value = (usize, Vec<TileSpec>)> {
+    (1..=5usize, 1..=5usize).prop_flat_map(|(width, height)| {
+        (
+            Just(width),
+            proptest::collection::vec(tile(), width * height),
+        )
+    })
+}
+
+/// Returns board string with rows separated by `/` (single row ends with `/`, so it isn't read as square board).
+fn board_string(width: usize, tiles: &[TileSpec]) -> String {
+    let mut string = tiles
+        .chunks(width)
+        .map(|row| row.iter().map(|x| x.to_board_string()).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("/");
+    if tiles.len() == width {
+        string.push('/');
+    }
+    string
+}
+
+/// Checks that `word` is a valid move on the board described by `width` and `tiles`, with at most `swaps` swaps.
+fn check_word(
+    board: &Board,
+    width: usize,
+    tiles: &[TileSpec],
+    swaps: u8,
+    word: &Word,
+) -> Result<(), TestCaseError> {
+    let (dictionary, words) = dictionary();
+    let string = word.word(board, false);
+    prop_assert!(!word.steps.is_empty(), "{string} has no steps");
+    prop_assert!(words.contains(&string), "{string} isn't in dictionary");
+    let mut used = HashSet::new();
+    let mut swaps_used = 0;
+    for (position, step) in word.steps.iter().enumerate() {
+        let index = step.index() as usize;
+        prop_assert!(
+            index < tiles.len(),
+            "{string} uses tile {index} outside of board"
+        );
+        prop_assert!(used.insert(index), "{string} uses tile {index} twice");
+        let tile = &tiles[index];
+        prop_assert!(!tile.frozen, "{string} uses frozen tile {index}");
+        if position > 0 {
+            let previous = word.steps[position - 1].index() as usize;
+            let (dx, dy) = (
+                (index % width).abs_diff(previous % width),
+                (index / width).abs_diff(previous / width),
+            );
+            prop_assert!(
+                dx <= 1 && dy <= 1,
+                "{string} goes from tile {previous} to tile {index}, which aren't adjacent"
+            );
+        }
+        match step {
+            Step::Normal { .. } => prop_assert!(tile.letter != '?'),
+            Step::Swap { new_letter, .. } => {
+                prop_assert!(tile.letter != '?' && tile.letter != *new_letter);
+                swaps_used += 1;
+            }
+            Step::Wildcard { .. } => prop_assert_eq!(tile.letter, '?'),
+        }
+        if let Step::Normal { .. } = step {
+            prop_assert_eq!(step.letter(board), tile.letter);
+        }
+    }
+    prop_assert!(swaps_used <= swaps, "{string} uses {swaps_used} swaps");
+    prop_assert_eq!(word.swaps_used, swaps_used);
+    let gems = word
+        .steps
+        .iter()
+        .filter(|x| tiles[x.index() as usize].gem)
+        .count();
+    prop_assert_eq!(word.gems_collected as usize, gems);
+    let recomputed = Word::new(word.steps.clone(), board);
+    prop_assert_eq!(recomputed.score, word.score);
+    prop_assert_eq!(recomputed.gems_collected, word.gems_collected);
+    prop_assert_eq!(recomputed.sorting_score, word.sorting_score);
+    prop_assert_eq!(word.breakdown(board).score, word.score);
+    let verified =
+        verify(board, word.steps.clone(), swaps, dictionary).map_err(TestCaseError::fail)?;
+    prop_assert_eq!(verified.sorting_score, word.sorting_score);
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn board_parsers_never_panic(s in "\\PC*") {
+        let _ = s.parse::<Board>();
+        let _ = Board::parse_strict(&s);
+        let _ = Board::from_json(&s);
+    }
+
+    #[test]
+    fn board_parsers_never_panic_on_board_syntax(s in "[a-z?$+*!#1-9/\n ]{0,80}") {
+        let _ = s.parse::<Board>();
+        let _ = Board::parse_strict(&s);
+    }
+
+    #[test]
+    fn json_board_parser_never_panics(
+        width in prop::option::of(any::<usize>()),
+        height in prop::option::of(any::<usize>()),
+        tiles in 0..30usize,
+        letter in "\\PC{0,2}",
+    ) {
+        let json = serde_json::json!({
+            "tiles": vec![serde_json::json!({ "letter": letter }); tiles],
+            "width": width,
+            "height": height,
+        });
+        let _ = Board::from_json(&json.to_string());
+    }
+
+    #[test]
+    fn board_string_round_trips((width, tiles) in board()) {
+        let string = board_string(width, &tiles);
+        let board = Board::parse_strict(&string).map_err(TestCaseError::fail)?;
+        prop_assert_eq!(board.width(), width);
+        prop_assert_eq!(board.height(), tiles.len() / width);
+        let canonical = board.to_string();
+        let reparsed: Board = canonical.parse().map_err(TestCaseError::fail)?;
+        prop_assert_eq!(reparsed.to_string(), canonical);
+        prop_assert_eq!((reparsed.width(), reparsed.height()), (board.width(), board.height()));
+    }
+
+    #[test]
+    fn dictionary_parser_keeps_every_word(s in "[a-c\n]{0,200}|\\PC{0,200}") {
+        let dictionary = load_dictionary_tree(s.clone());
+        let expected: BTreeSet<String> = s
+            .lines()
+            .filter(|x| x.chars().count() >= 3)
+            .map(String::from)
+            .collect();
+        let actual: BTreeSet<String> = dictionary_words(&dictionary).into_iter().collect();
+        prop_assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn dictionary_bytes_never_panic(bytes in proptest::collection::vec(any::<u8>(), 0..200)) {
+        let _ = load_dictionary_bytes(&bytes);
+    }
+}
+
+proptest! {
+    // Solving is much slower than parsing, so there are fewer cases.
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn solver_returns_valid_moves(
+        (width, tiles) in board(),
+        swaps in 0..=2u8,
+        threads in 1..=3u8,
+        move_count in 1..=10u8,
+    ) {
+        let (dictionary, _) = dictionary();
+        let board: Board = board_string(width, &tiles).parse().map_err(TestCaseError::fail)?;
+        let words = solver_wrapper(
+            &board,
+            swaps,
+            threads,
+            dictionary,
+            move_count,
+            &SolveOptions::default(),
+            SolveControl::default(),
+        );
+        prop_assert!(words.len() <= move_count as usize);
+        let mut strings = HashSet::new();
+        for (index, word) in words.iter().enumerate() {
+            check_word(&board, width, &tiles, swaps, word)?;
+            prop_assert!(strings.insert(word.word(&board, false)), "word is returned twice");
+            if index > 0 {
+                prop_assert!(words[index - 1].sorting_score >= word.sorting_score, "words aren't sorted");
+            }
+        }
+    }
+}
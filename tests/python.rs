@@ -0,0 +1,129 @@
+//! Tests for Python module (src/python.rs), run with `cargo test --features python --test python`.
+//! Module is imported into embedded interpreter and tests are written in Python, so they use it exactly like Python code does.
+#![cfg(feature = "python")]
+
+use std::{ffi::CString, sync::Once};
+
+use common::{DictionaryFile, SMALL_DICTIONARY};
+use pyo3::{prelude::*, types::PyDict, wrap_pymodule};
+use spellcast_solver::python::spellcast_solver;
+
+mod common;
+
+/// Runs Python `code` with module imported as `spellcast_solver` and `DICTIONARY` set to path of small dictionary.
+fn run(name: &str, code: &str) {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        pyo3::append_to_inittab!(spellcast_solver);
+        pyo3::prepare_freethreaded_python();
+    });
+    let file = DictionaryFile::new(name, SMALL_DICTIONARY);
+    let result = Python::with_gil(|py| -> PyResult<()> {
+        let globals = PyDict::new(py);
+        globals.set_item("spellcast_solver", wrap_pymodule!(spellcast_solver)(py))?;
+        globals.set_item("DICTIONARY", file.path().to_str().unwrap())?;
+        py.run(&CString::new(code).unwrap(), Some(&globals), None)
+    });
+    if let Err(e) = result {
+        panic!("{e}");
+    }
+}
+
+#[test]
+fn solves_board() {
+    run(
+        "solve",
+        r##"
+solver = spellcast_solver.Solver(DICTIONARY)
+assert solver.words == 9
+board = spellcast_solver.Board("ca$tsogred")
+assert (board.width, board.height, board.swaps) == (3, 3, None)
+words = solver.solve(board, count=3)
+assert len(words) == 3
+assert [x.score for x in words] == sorted([x.score for x in words], reverse=True)
+for word in words:
+    assert len(word.tiles) == len(word.word)
+    assert word.swaps == [] and word.wildcards == []
+# Board strings can be passed directly.
+assert [x.word for x in solver.solve("ca$tsogred", count=3)] == [x.word for x in words]
+"##,
+    );
+}
+
+#[test]
+fn reports_swaps_and_wildcards() {
+    run(
+        "steps",
+        r##"
+solver = spellcast_solver.Solver(DICTIONARY)
+# Wildcard on B1 can only be "a" in "cat" (A1 B1 C1).
+[word] = solver.solve("c?tsogxex", pattern="cat")
+assert (word.word, word.tiles, word.swaps_used) == ("cat", [0, 1, 2], 0)
+assert word.wildcards == [("B1", "a")] and word.swaps == []
+# Same word with swap instead of wildcard.
+[word] = solver.solve("cxtsogxex", swaps=1, pattern="cat")
+assert (word.word, word.swaps_used) == ("cat", 1)
+assert word.swaps == [("B1", "a")] and word.wildcards == []
+"##,
+    );
+}
+
+#[test]
+fn options_are_applied() {
+    run(
+        "options",
+        r##"
+solver = spellcast_solver.Solver(DICTIONARY)
+words = solver.solve("ca$tsogred", start="A3", pattern="*d")
+assert sorted(x.word for x in words) == ["red", "rod"]
+words = solver.solve("ca$tsogred", word_list=["dog"])
+assert [x.word for x in words] == ["dog"]
+"##,
+    );
+}
+
+#[test]
+fn strict_parsing_reports_problems() {
+    run(
+        "strict",
+        r##"
+solver = spellcast_solver.Solver(DICTIONARY)
+# Lenient parser ignores unknown characters.
+assert spellcast_solver.Board("ca%tsogred").width == 3
+assert solver.solve("ca%tsogred")
+for parse in [
+    lambda: spellcast_solver.Board("ca%tsogred", strict=True),
+    lambda: solver.solve("ca%tsogred", strict=True),
+]:
+    try:
+        parse()
+    except ValueError as e:
+        assert "unknown character" in str(e), e
+    else:
+        raise AssertionError("strict parsing should fail")
+"##,
+    );
+}
+
+#[test]
+fn invalid_input_raises_value_error() {
+    run(
+        "invalid",
+        r##"
+solver = spellcast_solver.Solver(DICTIONARY)
+for call in [
+    lambda: spellcast_solver.Solver("/nonexistent/dictionary.txt"),
+    lambda: spellcast_solver.Board("abc"),
+    lambda: solver.solve("ca$tsogred", start="Z9"),
+    lambda: solver.solve("ca$tsogred", adjacency="3way"),
+    lambda: solver.solve("ca$tsogred", regex="("),
+]:
+    try:
+        call()
+    except ValueError:
+        pass
+    else:
+        raise AssertionError("ValueError should be raised")
+"##,
+    );
+}
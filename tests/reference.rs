@@ -0,0 +1,326 @@
+//! Compares results of the fast solver with the reference solver (tests/common/reference.rs) on many seeded random boards.
+//! Reference solver is very slow, so both of them only use part of dictionary.txt and boards are small or few.
+//! Much longer comparison is ignored by default, run it with `cargo test --release --test reference -- --ignored`.
+
+use std::collections::{HashMap, HashSet};
+
+use common::reference::Reference;
+use spellcast_solver::{
+    dictionary::{load_dictionary_tree, Node},
+    random::Rng,
+    spellcast::{
+        solver_wrapper, Adjacency, Board, SolveControl, SolveOptions, Word, WordMultiplierRule,
+    },
+};
+
+mod common;
+
+const MOVE_COUNT: u8 = 10;
+/// Only every `DICTIONARY_STEP`th word is used by quick tests.
+const DICTIONARY_STEP: usize = 50;
+
+/// Returns every `step`th word of dictionary.txt as dictionary tree.
+fn dictionary(step: usize) -> Vec<(char, Node)> {
+    let content = std::fs::read_to_string("dictionary.txt").expect("dictionary.txt should exist");
+    let words: Vec<&str> = content.lines().step_by(step).collect();
+    load_dictionary_tree(words.join("\n"))
+}
+
+/// Returns random board string with random modifiers, gems, frozen and wildcard tiles.
+fn random_board(rng: &mut Rng, width: usize, height: usize) -> String {
+    let mut rows = vec![];
+    for _ in 0..height {
+        let mut row = String::new();
+        for _ in 0..width {
+            let roll = rng.next_f64();
+            if roll < 0.03 {
+                row.push('?');
+            } else {
+                // Vowels are more common, so boards have more words.
+                let vowel = rng.next_f64() < 0.4;
+                let letters = if vowel {
+                    "aeiou"
+                } else {
+                    "bcdfghjklmnpqrstvwxyz"
+                };
+                let index = (rng.next_u64() % letters.len() as u64) as usize;
+                row.push(letters.as_bytes()[index] as char);
+            }
+            match rng.next_f64() {
+                x if x < 0.05 => row.push('$'),
+                x if x < 0.07 => row.push_str("$3"),
+                _ => (),
+            }
+            match rng.next_f64() {
+                x if x < 0.08 => row.push('+'),
+                x if x < 0.12 => row.push('*'),
+                _ => (),
+            }
+            if rng.next_f64() < 0.2 {
+                row.push('!');
+            }
+            if rng.next_f64() < 0.06 {
+                row.push('#');
+            }
+        }
+        rows.push(row);
+    }
+    rows.join("/")
+}
+
+/// Returns random custom adjacency list, where every tile has 1-4 neighbors.
+fn random_adjacency(rng: &mut Rng, tiles: usize) -> Adjacency {
+    let list = (0..tiles)
+        .map(|index| {
+            let count = 1 + rng.next_u64() % 4;
+            let mut neighbors = vec![];
+            for _ in 0..count {
+                let neighbor = (rng.next_u64() % tiles as u64) as u16;
+                if neighbor as usize != index && !neighbors.contains(&neighbor) {
+                    neighbors.push(neighbor);
+                }
+            }
+            neighbors
+        })
+        .collect();
+    Adjacency::Custom(list)
+}
+
+/// Single comparison of both solvers.
+struct Case<'a> {
+    board: &'a str,
+    adjacency: Adjacency,
+    multiplier_rule: WordMultiplierRule,
+    swaps: u8,
+    threads: u8,
+    options: SolveOptions,
+}
+
+/// Solves the case with both solvers and panics with explanation if fast solver returned something reference solver disagrees with.
+/// Words with the same sorting score can come in any order, so only scores are compared position by position.
+fn check(dictionary: &Vec<(char, Node)>, case: Case) {
+    let label = format!(
+        "board {} ({:?}, {:?}, {} swaps, {} threads, {:?})",
+        case.board, case.adjacency, case.multiplier_rule, case.swaps, case.threads, case.options
+    );
+    let mut board: Board = case.board.parse().expect("random board should be valid");
+    board
+        .set_adjacency(&case.adjacency)
+        .expect("adjacency should match the board");
+    board.set_word_multiplier_rule(case.multiplier_rule);
+    let found = solver_wrapper(
+        &board,
+        case.swaps,
+        case.threads,
+        dictionary,
+        MOVE_COUNT,
+        &case.options,
+        SolveControl::default(),
+    );
+    let reference = Reference::new(&board, &case.adjacency, case.swaps, &case.options);
+    let expected = reference.solve(dictionary);
+    let best: HashMap<String, &Word> = expected
+        .iter()
+        .map(|x| (x.word(&board, false), x))
+        .collect();
+    let mut seen = HashSet::new();
+    for word in &found {
+        let string = word.word(&board, false);
+        assert!(
+            seen.insert(string.clone()),
+            "{label}: {string} is returned twice"
+        );
+        if let Err(e) = reference.check(&word.steps, &string) {
+            panic!("{label}: {string} breaks rules: {e} ({:?})", word.steps);
+        }
+        let Some(reference_word) = best.get(&string) else {
+            panic!("{label}: {string} isn't in dictionary");
+        };
+        assert_eq!(
+            word.sorting_score, reference_word.sorting_score,
+            "{label}: {string} isn't formed in the best way ({:?} instead of {:?})",
+            word.steps, reference_word.steps
+        );
+    }
+    let scores = |words: &[Word]| words.iter().map(|x| x.sorting_score).collect::<Vec<_>>();
+    let expected = &expected[..expected.len().min(MOVE_COUNT as usize)];
+    assert_eq!(
+        scores(&found),
+        scores(expected),
+        "{label}: found {:?}, but best words are {:?}",
+        found
+            .iter()
+            .map(|x| x.word(&board, false))
+            .collect::<Vec<_>>(),
+        expected
+            .iter()
+            .map(|x| x.word(&board, false))
+            .collect::<Vec<_>>()
+    );
+}
+
+/// Checks `count` random boards of `width`x`height` with every swap count in `swaps`.
+fn check_random(
+    dictionary: &Vec<(char, Node)>,
+    seed: u64,
+    count: usize,
+    (width, height): (usize, usize),
+    swaps: &[u8],
+) {
+    let mut rng = Rng::new(seed);
+    for _ in 0..count {
+        let board = random_board(&mut rng, width, height);
+        for swaps in swaps {
+            check(
+                dictionary,
+                Case {
+                    board: &board,
+                    adjacency: Adjacency::default(),
+                    multiplier_rule: WordMultiplierRule::Max,
+                    swaps: *swaps,
+                    threads: 1,
+                    options: SolveOptions::default(),
+                },
+            );
+        }
+    }
+}
+
+#[test]
+fn without_swaps() {
+    check_random(&dictionary(DICTIONARY_STEP), 1, 40, (5, 5), &[0]);
+}
+
+#[test]
+fn with_swaps() {
+    let dictionary = dictionary(DICTIONARY_STEP);
+    check_random(&dictionary, 2, 5, (5, 5), &[1]);
+    check_random(&dictionary, 3, 5, (4, 4), &[2]);
+    check_random(&dictionary, 4, 3, (3, 3), &[3]);
+}
+
+#[test]
+fn with_deep_swaps() {
+    check_random(&dictionary(DICTIONARY_STEP), 5, 2, (3, 3), &[4]);
+}
+
+#[test]
+fn other_board_sizes() {
+    let dictionary = dictionary(DICTIONARY_STEP);
+    check_random(&dictionary, 6, 5, (6, 6), &[0, 1]);
+    check_random(&dictionary, 7, 5, (7, 3), &[0, 1]);
+    check_random(&dictionary, 8, 5, (2, 2), &[0, 2]);
+}
+
+#[test]
+#[ignore = "takes several minutes even with --release"]
+fn many_boards() {
+    let dictionary = dictionary(10);
+    check_random(&dictionary, 100, 200, (5, 5), &[0, 1]);
+    check_random(&dictionary, 101, 50, (5, 5), &[2]);
+    check_random(&dictionary, 102, 20, (4, 4), &[3]);
+    check_random(&dictionary, 103, 10, (3, 3), &[4, 5]);
+}
+
+#[test]
+fn adjacency_rules() {
+    let dictionary = dictionary(DICTIONARY_STEP);
+    let mut rng = Rng::new(9);
+    for _ in 0..10 {
+        let board = random_board(&mut rng, 5, 4);
+        let custom = random_adjacency(&mut rng, 20);
+        let adjacencies = [
+            Adjacency::from_name("4way").unwrap(),
+            Adjacency::from_name("8way-wrap").unwrap(),
+            Adjacency::from_name("4way-wrap").unwrap(),
+            custom,
+        ];
+        for adjacency in adjacencies {
+            check(
+                &dictionary,
+                Case {
+                    board: &board,
+                    adjacency,
+                    multiplier_rule: WordMultiplierRule::Max,
+                    swaps: 1,
+                    threads: 1,
+                    options: SolveOptions::default(),
+                },
+            );
+        }
+    }
+}
+
+#[test]
+fn stacking_multipliers_and_threads() {
+    let dictionary = dictionary(DICTIONARY_STEP);
+    let mut rng = Rng::new(10);
+    for _ in 0..6 {
+        let board = random_board(&mut rng, 5, 5);
+        for (multiplier_rule, threads) in [
+            (WordMultiplierRule::Multiply, 1),
+            (WordMultiplierRule::Max, 4),
+        ] {
+            check(
+                &dictionary,
+                Case {
+                    board: &board,
+                    adjacency: Adjacency::default(),
+                    multiplier_rule,
+                    swaps: 1,
+                    threads,
+                    options: SolveOptions::default(),
+                },
+            );
+        }
+    }
+}
+
+#[test]
+fn solve_options() {
+    let dictionary = dictionary(DICTIONARY_STEP);
+    let mut rng = Rng::new(11);
+    for _ in 0..6 {
+        let board = random_board(&mut rng, 5, 5);
+        let mut tile = || (rng.next_u64() % 25) as u16;
+        let options = [
+            SolveOptions {
+                include: vec![tile()],
+                exclude: vec![tile(), tile()],
+                ..Default::default()
+            },
+            SolveOptions {
+                start: vec![tile(), tile(), tile()],
+                end: vec![tile(), tile(), tile(), tile()],
+                ..Default::default()
+            },
+            SolveOptions {
+                min_length: 5,
+                max_length: Some(6),
+                ..Default::default()
+            },
+            SolveOptions {
+                patterns: vec![spellcast_solver::spellcast::pattern_to_regex("*e*").unwrap()],
+                word_list: Some(
+                    ["tea", "eat", "seat", "east", "teas", "rest"]
+                        .map(String::from)
+                        .into(),
+                ),
+                ..Default::default()
+            },
+        ];
+        for options in options {
+            check(
+                &dictionary,
+                Case {
+                    board: &board,
+                    adjacency: Adjacency::default(),
+                    multiplier_rule: WordMultiplierRule::Max,
+                    swaps: 1,
+                    threads: 1,
+                    options,
+                },
+            );
+        }
+    }
+}
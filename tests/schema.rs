@@ -0,0 +1,148 @@
+//! Tests that real JSON output of the CLI (`-f json` and `batch`) and server responses match [schema/output.v1.json](../schema/output.v1.json).
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::{
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use common::{solver, DictionaryFile, SMALL_DICTIONARY};
+use jsonschema::Validator;
+use serde_json::{json, Value};
+
+mod common;
+
+/// Boards with every kind of tile: modifiers, gems, frozen and wildcard tiles (and a non-square one).
+const BOARDS: [&str; 4] = [
+    "ca$tsogred",
+    "c!a$t!/s#o+?/re!d",
+    r#"{"tiles": [{"letter": "c", "gem": true}, {"letter": "?"}, {"letter": "t", "word_multiplier": 3}, {"letter": "s"}, {"letter": "o", "letter_multiplier": 3}, {"letter": "d", "frozen": true}, {"letter": "r"}, {"letter": "e"}, {"letter": "d"}], "gems": 6}"#,
+    "cats/oger",
+];
+
+fn validator() -> Validator {
+    let schema: Value = serde_json::from_str(
+        &std::fs::read_to_string("schema/output.v1.json").expect("schema should exist"),
+    )
+    .expect("schema should be valid JSON");
+    jsonschema::validator_for(&schema).expect("schema should be valid")
+}
+
+/// Panics with every schema error of `output`.
+fn assert_valid(validator: &Validator, output: &Value) {
+    let errors: Vec<String> = validator
+        .iter_errors(output)
+        .map(|e| format!("{} at {}", e, e.instance_path))
+        .collect();
+    assert!(errors.is_empty(), "{output}\n{}", errors.join("\n"));
+}
+
+/// Runs the CLI with `args` and `stdin`, returns its stdout.
+fn run(dictionary: &Path, args: &[&str], stdin: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_spellcast-solver"))
+        .arg("-d")
+        .arg(dictionary)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("CLI should start");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "{args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).expect("output should be UTF-8")
+}
+
+/// Options that add something to `options` of the output.
+const OPTIONS: [&[&str]; 4] = [
+    &[],
+    &["-s", "2", "-c", "10"],
+    &[
+        "--include",
+        "B2",
+        "--exclude",
+        "A1",
+        "--min-length",
+        "3",
+        "--max-length",
+        "4",
+    ],
+    &[
+        "--start",
+        "A3",
+        "--end",
+        "C3",
+        "--pattern",
+        "r*",
+        "--regex",
+        "d$",
+    ],
+];
+
+#[test]
+fn json_format_matches_schema() {
+    let validator = validator();
+    let dictionary = DictionaryFile::new("json", SMALL_DICTIONARY);
+    for board in BOARDS {
+        for options in OPTIONS {
+            if board.starts_with("cats/") && options.contains(&"C3") {
+                // No third row on this board.
+                continue;
+            }
+            let mut args = vec!["-f", "json", "-b", board];
+            args.extend_from_slice(options);
+            let output: Value = serde_json::from_str(&run(dictionary.path(), &args, ""))
+                .unwrap_or_else(|e| panic!("{args:?}: {e}"));
+            assert_valid(&validator, &output);
+        }
+    }
+}
+
+#[test]
+fn batch_output_matches_schema() {
+    let validator = validator();
+    let dictionary = DictionaryFile::new("batch", SMALL_DICTIONARY);
+    let input = format!("{}\nabc\n", BOARDS.join("\n"));
+    let output = run(dictionary.path(), &["-s", "1", "batch"], &input);
+    let lines: Vec<Value> = output
+        .lines()
+        .map(|x| serde_json::from_str(x).expect("line should be valid JSON"))
+        .collect();
+    assert_eq!(lines.len(), BOARDS.len() + 1);
+    for line in &lines[..BOARDS.len()] {
+        assert_valid(&validator, line);
+    }
+    // Invalid boards get error line, which isn't output of a solve.
+    let error = &lines[BOARDS.len()];
+    assert_eq!(error["line"], BOARDS.len() + 1);
+    assert!(error["error"].is_string());
+    assert!(!validator.is_valid(error));
+}
+
+#[test]
+fn server_responses_match_schema() {
+    let validator = validator();
+    let solver = solver(SMALL_DICTIONARY, 1);
+    for board in BOARDS {
+        let board = serde_json::from_str(board).unwrap_or(json!(board));
+        let request = json!({"id": 1, "board": board, "swaps": 1, "adjacency": "4way"});
+        let partial = |response: String| {
+            assert_valid(&validator, &serde_json::from_str(&response).unwrap());
+        };
+        let response = solver
+            .handle_live(&request.to_string(), None, Some(&partial))
+            .unwrap();
+        assert_valid(&validator, &serde_json::from_str(&response).unwrap());
+    }
+}
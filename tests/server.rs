@@ -0,0 +1,372 @@
+//! Tests of server modes (src/server.rs) on ephemeral ports: HTTP routes and CORS, WebSocket cancellation and connection and request size limits.
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+use serde_json::{json, Value};
+use spellcast_solver::{
+    api::Solver,
+    server,
+    utils::{MAX_CONNECTIONS, MAX_REQUEST_BYTES},
+};
+use tungstenite::{stream::MaybeTlsStream, Message, WebSocket};
+
+mod common;
+
+const BOARD: &str = "dwtgmlqucavltalsfyxaaoyjf";
+
+/// Solver with the full dictionary (so some searches take long enough to be cancelled) and two threads, shared by all tests.
+fn solver() -> &'static Solver {
+    static SOLVER: OnceLock<Solver> = OnceLock::new();
+    SOLVER.get_or_init(|| {
+        common::solver(
+            &std::fs::read_to_string("dictionary.txt").expect("dictionary.txt should exist"),
+            2,
+        )
+    })
+}
+
+/// Binds ephemeral port, runs `serve` on it in background and returns its address.
+fn start(serve: fn(TcpListener)) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    std::thread::spawn(move || serve(listener));
+    address
+}
+
+fn start_http() -> SocketAddr {
+    start(|listener| {
+        let cors = [String::from("https://allowed.example")];
+        server::serve_http(solver(), listener, &cors).unwrap()
+    })
+}
+
+fn start_websocket() -> SocketAddr {
+    start(|listener| server::serve_websocket(solver(), listener).unwrap())
+}
+
+/// HTTP response: status, headers (with lowercase names) and body.
+struct HttpResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl HttpResponse {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|x| x.0 == name)
+            .map(|x| x.1.as_str())
+    }
+
+    fn json(&self) -> Value {
+        serde_json::from_str(&self.body).expect("body should be valid JSON")
+    }
+}
+
+/// Sends HTTP request with `extra_headers` (each ending with `\r\n`) and reads the response.
+fn http(
+    address: SocketAddr,
+    method: &str,
+    path: &str,
+    extra_headers: &str,
+    body: &str,
+) -> HttpResponse {
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(
+        stream,
+        "{method} {path} HTTP/1.1\r\nHost: {address}\r\nConnection: close\r\nContent-Length: {}\r\n{extra_headers}\r\n{body}",
+        body.len()
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .expect("response should have head");
+    let mut lines = head.lines();
+    let status = lines
+        .next()
+        .unwrap()
+        .split(' ')
+        .nth(1)
+        .unwrap()
+        .parse()
+        .unwrap();
+    let headers = lines
+        .filter_map(|x| x.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_owned()))
+        .collect();
+    HttpResponse {
+        status,
+        headers,
+        body: body.to_owned(),
+    }
+}
+
+#[test]
+fn http_routes() {
+    let address = start_http();
+    let response = http(address, "GET", "/health", "", "");
+    assert_eq!(response.status, 200);
+    assert_eq!(response.header("content-type"), Some("application/json"));
+    assert_eq!(response.json(), json!({"status": "ok"}));
+
+    let response = http(address, "GET", "/dictionary?x=1", "", "");
+    assert_eq!(response.status, 200);
+    assert_eq!(response.json()["words"], solver().dictionary_info.words);
+
+    let request = json!({"id": "http", "board": BOARD, "count": 3}).to_string();
+    let response = http(address, "POST", "/solve", "", &request);
+    assert_eq!(response.status, 200);
+    let json = response.json();
+    assert_eq!(json["id"], "http");
+    assert_eq!(json["words"].as_array().unwrap().len(), 3);
+
+    let response = http(
+        address,
+        "POST",
+        "/solve",
+        "",
+        r#"{"id": 2, "board": "abc"}"#,
+    );
+    assert_eq!(response.status, 400);
+    assert_eq!(response.json()["id"], 2);
+    assert!(response.json()["error"].is_string());
+
+    for (method, path, status) in [
+        ("GET", "/solve", 405),
+        ("POST", "/health", 405),
+        ("GET", "/", 404),
+        ("POST", "/unknown", 404),
+    ] {
+        let response = http(address, method, path, "", "");
+        assert_eq!(response.status, status, "{method} {path}");
+        assert!(response.json()["error"].is_string());
+    }
+}
+
+#[test]
+fn http_cors_headers() {
+    let address = start_http();
+    let allowed = "Origin: https://allowed.example\r\n";
+    let response = http(address, "OPTIONS", "/solve", allowed, "");
+    assert_eq!(response.status, 204);
+    assert_eq!(
+        response.header("access-control-allow-origin"),
+        Some("https://allowed.example")
+    );
+    assert_eq!(
+        response.header("access-control-allow-methods"),
+        Some("GET, POST, OPTIONS")
+    );
+    assert_eq!(
+        response.header("access-control-allow-headers"),
+        Some("Content-Type")
+    );
+    assert_eq!(response.header("vary"), Some("Origin"));
+    let response = http(address, "GET", "/health", allowed, "");
+    assert_eq!(
+        response.header("access-control-allow-origin"),
+        Some("https://allowed.example")
+    );
+    // Other origins and requests without origin don't get any CORS headers.
+    for headers in ["Origin: https://other.example\r\n", ""] {
+        let response = http(address, "GET", "/health", headers, "");
+        assert_eq!(response.status, 200);
+        assert!(response
+            .headers
+            .iter()
+            .all(|x| !x.0.starts_with("access-control-")));
+    }
+}
+
+#[test]
+fn http_body_is_limited() {
+    let address = start_http();
+    let request = json!({"board": BOARD, "word_list": ["a".repeat(MAX_REQUEST_BYTES)]}).to_string();
+    let response = http(address, "POST", "/solve", "", &request);
+    assert_eq!(response.status, 413);
+    assert!(response.json()["error"]
+        .as_str()
+        .unwrap()
+        .contains("longer than"));
+    // Server still works afterwards.
+    assert_eq!(http(address, "GET", "/health", "", "").status, 200);
+}
+
+type Socket = WebSocket<MaybeTlsStream<TcpStream>>;
+
+fn connect(address: SocketAddr) -> Result<Socket, Box<tungstenite::Error>> {
+    let (socket, _) = tungstenite::connect(format!("ws://{address}"))?;
+    if let MaybeTlsStream::Plain(stream) = socket.get_ref() {
+        stream
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+    }
+    Ok(socket)
+}
+
+fn send(socket: &mut Socket, request: Value) {
+    socket.send(Message::Text(request.to_string())).unwrap();
+}
+
+fn receive(socket: &mut Socket) -> Value {
+    loop {
+        match socket.read().expect("response should arrive") {
+            Message::Text(text) => return serde_json::from_str(&text).unwrap(),
+            Message::Binary(_) => panic!("responses should be text"),
+            _ => {}
+        }
+    }
+}
+
+/// Reads responses until final (not partial) one and returns all of them.
+fn receive_final(socket: &mut Socket) -> Vec<Value> {
+    let mut responses = vec![];
+    loop {
+        let response = receive(socket);
+        let done = response.get("partial").is_none();
+        responses.push(response);
+        if done {
+            return responses;
+        }
+    }
+}
+
+#[test]
+fn websocket_streams_results() {
+    let mut socket = connect(start_websocket()).unwrap();
+    send(
+        &mut socket,
+        json!({"id": 1, "board": BOARD, "swaps": 1, "count": 3}),
+    );
+    let responses = receive_final(&mut socket);
+    let last = responses.last().unwrap();
+    assert_eq!(last["id"], 1);
+    assert_eq!(last["words"].as_array().unwrap().len(), 3);
+    for partial in &responses[..responses.len() - 1] {
+        assert_eq!(partial["id"], 1);
+        assert_eq!(partial["partial"], true);
+    }
+    // Errors and binary messages get error responses, connection stays open.
+    send(&mut socket, json!({"id": 2, "board": "abc"}));
+    let error = receive(&mut socket);
+    assert_eq!(error["id"], 2);
+    assert!(error["error"].is_string());
+    socket.send(Message::Binary(vec![1, 2, 3])).unwrap();
+    assert_eq!(receive(&mut socket)["error"], "Expected text message");
+    send(&mut socket, json!({"id": 3, "board": BOARD}));
+    assert_eq!(receive_final(&mut socket).last().unwrap()["id"], 3);
+}
+
+#[test]
+fn websocket_new_request_cancels_previous() {
+    let mut socket = connect(start_websocket()).unwrap();
+    // Words have to start on one of the first two tiles of a board full of wildcards, it takes minutes if it isn't cancelled.
+    let slow_board = format!("s{}", "?".repeat(24));
+    send(
+        &mut socket,
+        json!({"id": "slow", "board": slow_board, "start": "A1,B1"}),
+    );
+    std::thread::sleep(Duration::from_millis(300));
+    let clock = Instant::now();
+    send(
+        &mut socket,
+        json!({"id": "fast", "board": BOARD, "swaps": 1}),
+    );
+    // Both solver threads are busy with the slow search until it is cancelled, so fast one only finishes after that.
+    let responses = receive_final(&mut socket);
+    assert!(
+        clock.elapsed() < Duration::from_secs(5),
+        "new request took {:?}",
+        clock.elapsed()
+    );
+    // Partial results of slow request can still arrive before the new one is read, but its final results never do.
+    let (slow, fast): (Vec<_>, Vec<_>) = responses.iter().partition(|x| x["id"] == "slow");
+    assert!(slow.iter().all(|x| x["partial"] == true));
+    assert_eq!(fast.last().unwrap()["id"], "fast");
+    assert!(fast.last().unwrap().get("partial").is_none());
+    // Only results of the latest request are sent from now on.
+    send(&mut socket, json!({"id": "last", "board": BOARD}));
+    for response in receive_final(&mut socket) {
+        assert_eq!(response["id"], "last");
+    }
+}
+
+#[test]
+fn websocket_message_size_is_limited() {
+    let mut socket = connect(start_websocket()).unwrap();
+    socket
+        .send(Message::Text("a".repeat(MAX_REQUEST_BYTES + 1)))
+        .unwrap();
+    let error = receive(&mut socket);
+    assert!(error["error"].as_str().unwrap().contains("longer than"));
+}
+
+#[test]
+fn websocket_connections_are_limited() {
+    let address = start_websocket();
+    let mut sockets: Vec<_> = (0..MAX_CONNECTIONS)
+        .map(|_| connect(address).expect("connection under the limit should be accepted"))
+        .collect();
+    match connect(address).map_err(|e| *e) {
+        Err(tungstenite::Error::Http(response)) => assert_eq!(response.status(), 503),
+        Err(e) => panic!("connection over the limit should get 503, got {e}"),
+        Ok(_) => panic!("connection over the limit should be refused"),
+    }
+    // Closed connection frees its slot.
+    sockets.pop().unwrap().close(None).unwrap();
+    let clock = Instant::now();
+    while connect(address).is_err() {
+        assert!(
+            clock.elapsed() < Duration::from_secs(5),
+            "slot should be freed"
+        );
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn socket_limits() {
+    use std::os::unix::net::UnixStream;
+    let path = std::env::temp_dir().join(format!("spellcast-server-{}.sock", std::process::id()));
+    let server_path = path.to_str().unwrap().to_owned();
+    std::thread::spawn(move || server::serve_socket(solver(), &server_path).unwrap());
+    let clock = Instant::now();
+    let connect = || loop {
+        match UnixStream::connect(&path) {
+            Ok(stream) => return stream,
+            Err(e) if clock.elapsed() > Duration::from_secs(5) => panic!("{e}"),
+            Err(_) => std::thread::sleep(Duration::from_millis(20)),
+        }
+    };
+    let mut stream = connect();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut read_line = || {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        serde_json::from_str::<Value>(&line).unwrap()
+    };
+    // Too long line gets an error, but the next one is still handled.
+    writeln!(stream, "{}", "a".repeat(MAX_REQUEST_BYTES + 1)).unwrap();
+    writeln!(stream, "{}", json!({"id": 1, "board": BOARD})).unwrap();
+    assert!(read_line()["error"]
+        .as_str()
+        .unwrap()
+        .contains("longer than"));
+    assert_eq!(read_line()["id"], 1);
+    // First connection is still open, so only MAX_CONNECTIONS - 1 more fit.
+    let _streams: Vec<_> = (1..MAX_CONNECTIONS).map(|_| connect()).collect();
+    let mut line = String::new();
+    BufReader::new(connect()).read_line(&mut line).unwrap();
+    assert!(line.contains("Too many connections"), "{line}");
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,235 @@
+//! Tests of move verification ([verify]) and parsing of JSON steps ([Step::list_from_json]).
+
+use spellcast_solver::{
+    dictionary::{load_dictionary_tree, Node},
+    spellcast::{solver_wrapper, verify, Adjacency, Board, SolveControl, SolveOptions, Step},
+};
+
+/// 3x3 board:
+/// ```text
+/// c  a$ t!
+/// s# o+ ?
+/// r  e! d
+/// ```
+const BOARD: &str = "ca$t!/s#o+?/re!d";
+
+fn dictionary() -> Vec<(char, Node)> {
+    load_dictionary_tree(String::from(
+        "cat\ncats\ncoat\ncoed\ncod\ncore\ndoe\nred\nrod\ntoe",
+    ))
+}
+
+fn board() -> Board {
+    BOARD.parse().expect("board should be valid")
+}
+
+fn normal(index: u16) -> Step {
+    Step::Normal { index }
+}
+
+/// Returns error of invalid move.
+fn error(board: &Board, steps: Vec<Step>, swaps: u8) -> String {
+    match verify(board, steps, swaps, &dictionary()) {
+        Ok(word) => panic!(
+            "move should be invalid, but got {}",
+            word.word(board, false)
+        ),
+        Err(e) => e,
+    }
+}
+
+#[test]
+fn valid_moves_are_scored() {
+    let board = board();
+    // c a t: (5+1+2)x2 with gem on t.
+    let word = verify(
+        &board,
+        vec![normal(0), normal(1), normal(2)],
+        0,
+        &dictionary(),
+    )
+    .expect("cat should be valid");
+    assert_eq!(word.word(&board, false), "cat");
+    assert_eq!(
+        (word.score, word.gems_collected, word.swaps_used),
+        (16, 1, 0)
+    );
+    // c o d, o is on DL tile.
+    let steps = vec![normal(0), normal(4), normal(8)];
+    let word = verify(&board, steps, 0, &dictionary()).expect("cod should be valid");
+    assert_eq!((word.score, word.gems_collected), (5 + 2 + 3, 0));
+    // d o e with wildcard used as e.
+    let steps = vec![
+        normal(8),
+        normal(4),
+        Step::Wildcard {
+            index: 5,
+            letter: 'e',
+        },
+    ];
+    let word = verify(&board, steps, 0, &dictionary()).expect("doe should be valid");
+    assert_eq!(
+        (word.score, word.gems_collected, word.swaps_used),
+        (3 + 2 + 1, 0, 0)
+    );
+    // c a t s with o swapped to s.
+    let steps = vec![
+        normal(0),
+        normal(1),
+        normal(2),
+        Step::Swap {
+            index: 4,
+            new_letter: 's',
+        },
+    ];
+    assert!(error(&board, steps.clone(), 0).contains("1 swaps"));
+    let word = verify(&board, steps, 1, &dictionary()).expect("cats should be valid");
+    assert_eq!(
+        (word.score, word.gems_collected, word.swaps_used),
+        ((5 + 1 + 2 + 2 * 2) * 2, 1, 1)
+    );
+}
+
+#[test]
+fn invalid_moves_are_rejected() {
+    let board = board();
+    let cases = [
+        (vec![], "no steps"),
+        (
+            vec![normal(0), normal(1), normal(20)],
+            "outside of the board",
+        ),
+        (vec![normal(3), normal(4), normal(0)], "frozen"),
+        (vec![normal(0), normal(1), normal(0)], "used twice"),
+        (vec![normal(0), normal(1), normal(6)], "isn't adjacent"),
+        (
+            vec![normal(4), normal(5), normal(8)],
+            "wildcard, so it needs a letter",
+        ),
+        (vec![normal(2), normal(4), normal(1)], "isn't in dictionary"),
+        (
+            vec![
+                normal(0),
+                normal(1),
+                Step::Swap {
+                    index: 2,
+                    new_letter: 't',
+                },
+            ],
+            "letter it already has",
+        ),
+        (
+            vec![
+                normal(8),
+                normal(4),
+                Step::Swap {
+                    index: 5,
+                    new_letter: 'e',
+                },
+            ],
+            "can't be swapped",
+        ),
+        (
+            vec![
+                normal(0),
+                Step::Wildcard {
+                    index: 1,
+                    letter: 'a',
+                },
+                normal(2),
+            ],
+            "isn't a wildcard",
+        ),
+    ];
+    for (steps, expected) in cases {
+        let e = error(&board, steps.clone(), 3);
+        assert!(
+            e.contains(expected),
+            "{steps:?}: expected {expected:?}, got {e:?}"
+        );
+    }
+}
+
+#[test]
+fn adjacency_rule_is_used() {
+    let mut board = board();
+    let steps = vec![normal(2), normal(4), normal(7)];
+    assert!(verify(&board, steps.clone(), 0, &dictionary()).is_ok());
+    board
+        .set_adjacency(&Adjacency::from_name("4way").unwrap())
+        .unwrap();
+    assert!(error(&board, steps, 0).contains("isn't adjacent"));
+    board
+        .set_adjacency(&Adjacency::Custom(vec![
+            vec![],
+            vec![],
+            vec![0],
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+        ]))
+        .unwrap();
+    // c a t backwards isn't a word, but t -> c is allowed by custom adjacency list and c -> a isn't.
+    assert!(error(&board, vec![normal(2), normal(0), normal(1)], 0).contains("isn't adjacent"));
+}
+
+#[test]
+fn steps_are_parsed_from_json() {
+    let steps = Step::list_from_json(
+        r#"[{"swap":false,"index":0},{"index":4},{"swap":true,"index":7,"new_letter":"E"},{"wildcard":true,"index":5,"new_letter":"d"}]"#,
+    )
+    .expect("steps should be valid");
+    assert_eq!(
+        format!("{steps:?}"),
+        "[Normal { index: 0 }, Normal { index: 4 }, Swap { index: 7, new_letter: 'e' }, Wildcard { index: 5, letter: 'd' }]"
+    );
+    for (json, expected) in [
+        ("[", "Invalid JSON"),
+        (r#"{"index":0}"#, "Invalid steps"),
+        (r#"[{"index":0,"letter":"a"}]"#, "unknown field"),
+        (r#"[{"index":0,"new_letter":"a"}]"#, "only allowed"),
+        (r#"[{"swap":true,"index":0}]"#, "need new_letter"),
+        (
+            r#"[{"swap":true,"wildcard":true,"index":0,"new_letter":"a"}]"#,
+            "both swap and wildcard",
+        ),
+    ] {
+        match Step::list_from_json(json) {
+            Ok(steps) => panic!("{json} should be invalid, but got {steps:?}"),
+            Err(e) => assert!(
+                e.contains(expected),
+                "{json}: expected {expected:?}, got {e:?}"
+            ),
+        }
+    }
+}
+
+#[test]
+fn solver_output_verifies() {
+    let board = board();
+    let dictionary = dictionary();
+    let words = solver_wrapper(
+        &board,
+        1,
+        1,
+        &dictionary,
+        10,
+        &SolveOptions::default(),
+        SolveControl::default(),
+    );
+    assert!(!words.is_empty());
+    for word in words {
+        // Word object from JSON output is accepted as is.
+        let json = serde_json::to_string(&word).unwrap();
+        let steps = Step::list_from_json(&json).expect("output steps should parse");
+        let verified = verify(&board, steps, 1, &dictionary).expect("solver word should be valid");
+        assert_eq!(
+            (verified.score, verified.gems_collected, verified.swaps_used),
+            (word.score, word.gems_collected, word.swaps_used)
+        );
+        assert_eq!(verified.word(&board, false), word.word(&board, false));
+    }
+}
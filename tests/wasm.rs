@@ -0,0 +1,64 @@
+//! Tests for JavaScript API (src/wasm.rs), they run in Node.js:
+//! `cargo test --target wasm32-unknown-unknown --features wasm --test wasm` (needs `wasm-bindgen-test-runner`, see README.md).
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use spellcast_solver::{
+    dictionary::load_dictionary_tree,
+    wasm::{normalize_board, Solver},
+};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+const DICTIONARY: &str = "cat\nact\ntax\ndog\n";
+
+/// Returns words from JSON results.
+fn words(results: &str) -> Vec<String> {
+    let results: serde_json::Value = serde_json::from_str(results).unwrap();
+    results["words"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|x| x["word"].as_str().unwrap().to_owned())
+        .collect()
+}
+
+#[wasm_bindgen_test]
+fn solves_with_text_dictionary() {
+    let Ok(solver) = Solver::new(DICTIONARY.as_bytes()) else {
+        panic!("dictionary should load");
+    };
+    assert_eq!(solver.words(), 4);
+    let Ok(results) = solver.solve(r#"{"board": "catxxxxxx"}"#) else {
+        panic!("board should be solved");
+    };
+    assert_eq!(words(&results), ["tax", "cat"]);
+}
+
+#[wasm_bindgen_test]
+fn solves_with_binary_dictionary() {
+    let binary = bincode::serialize(&load_dictionary_tree(DICTIONARY.into())).unwrap();
+    let Ok(solver) = Solver::new(&binary) else {
+        panic!("binary dictionary should load");
+    };
+    assert_eq!(solver.words(), 4);
+    let Ok(results) = solver.solve(r#"{"board": "catxxxxxx", "swaps": 1, "count": 10}"#) else {
+        panic!("board should be solved");
+    };
+    // "act" needs one of x tiles swapped to t.
+    assert!(words(&results).contains(&String::from("act")));
+}
+
+#[wasm_bindgen_test]
+fn rejects_invalid_input() {
+    let Ok(solver) = Solver::new(DICTIONARY.as_bytes()) else {
+        panic!("dictionary should load");
+    };
+    assert!(solver.solve(r#"{"board": "cat"}"#).is_err());
+    assert!(solver
+        .solve(r#"{"board": "catxxxxxx", "swap": 1}"#)
+        .is_err());
+    assert!(normalize_board("cat", false).is_err());
+    assert_eq!(
+        normalize_board("CATXXXXXX", true).ok().as_deref(),
+        Some("catxxxxxx")
+    );
+}